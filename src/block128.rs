@@ -16,6 +16,20 @@ impl Block128 {
 	pub fn get_b(&self) -> u64 {
 		self.0 as u64
 	}
+
+	// Multiplication by x in GF(2^128) modulo x^128 + x^7 + x^2 + x + 1, big-endian (CMAC, OCB, PMAC, SIV)
+	pub fn double(&self) -> Block128 {
+		Block128((self.0 << 1) ^ (0x87 & (self.0 >> 127).wrapping_neg()))
+	}
+
+	// Division by x, the inverse of double (PMAC)
+	pub fn halve(&self) -> Block128 {
+		Block128((self.0 >> 1) ^ (0x80000000000000000000000000000043 & (self.0 & 1).wrapping_neg()))
+	}
+
+	pub fn to_bytes(&self) -> [u8; BYTES_IN_BLOCK] {
+		self.0.to_be_bytes()
+	}
 }
 
 impl BitXor for Block128 {
@@ -125,4 +139,17 @@ fn block128_panic() {
 	let input1: &[u8] = &[0x74, 0x69, 0x20, 0x65, 0x61, 0x6d, 0x20, 0x6c, 0x61, 0x76, 0x69, 0x75, 0x71, 0x65, 0x20];
 	let block1: Block128 = Block128::from(input1);
 	assert_eq!(block1.0, 0x74692065616d206c61766975716520);
-}
\ No newline at end of file
+}
+
+#[test]
+fn block128_double() {
+	let block1: Block128 = Block128(0x7469206564616d206c61766975716520);
+	assert_eq!(block1.double(), Block128(0xe8d240cac8c2da40d8c2ecd2eae2ca40));
+
+	//Carry out of the top bit is reduced
+	let block2: Block128 = Block128(0x80000000000000000000000000000001);
+	assert_eq!(block2.double(), Block128(0x85));
+	assert_eq!(block2.to_bytes()[0], 0x80);
+	assert_eq!(block2.double().halve(), block2);
+	assert_eq!(block1.halve().double(), block1);
+}
@@ -0,0 +1,16 @@
+// Common interface of block ciphers, so modes of operation (cbc, etc.) can be used with any of them.
+// Blocks are passed as bytes; each cipher is responsible for mapping them to its words.
+
+// Errors of the modes of operation, CounterOverflow is returned by counter based modes only
+#[derive(Debug)]	//For "unwrap" in tests
+pub enum CipherErrors {WrongPadding, WrongInput, CounterOverflow}
+
+pub trait BlockCipher {
+	const BLOCK_SIZE: usize;
+	const KEY_SIZE: usize;
+
+	// For modes that derive their own keys, such as gcm_siv
+	fn from_key_bytes(key: &[u8]) -> Self where Self: Sized;
+	fn encrypt_block(&self, block: &mut [u8]);
+	fn decrypt_block(&self, block: &mut [u8]);
+}
@@ -2,35 +2,52 @@
 
 use block128;
 use block128::Block128;
+use block_cipher::BlockCipher;
+pub use block_cipher::CipherErrors;
 use speck_128_128::Speck_128_128;
 use padding::PaddingGenerator;
-use pkcs7::PKCS7;
 use util;
+use std::iter;
 use std::marker::PhantomData;
+#[cfg(test)]
+use pkcs7::PKCS7;
+#[cfg(test)]
+use speck::{Speck_32_64, Speck_64_128};
+#[cfg(test)]
+use simon::Simon_64_128;
 
 pub const BYTES_IN_WORD: usize = 8;
 pub const WORDS_IN_BLOCK: usize = 2;
 
-pub enum CipherErrors {WrongPadding, WrongInput}
 
-pub struct CBC <PG> {
-	iv: Block128,
-	block_cipher: Speck_128_128,
+// Ciphertext stealing variants from NIST SP 800-38A Addendum, they differ only in order of the last two blocks:
+// CS1 keeps the partial block before the last one, CS3 always swaps them (Kerberos),
+// CS2 swaps them only when the last plaintext block is incomplete.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CiphertextStealing {CS1, CS2, CS3}
+
+pub struct CBC <PG, BC = Speck_128_128> {
+	iv: Vec<u8>,
+	block_cipher: BC,
 	padd_generator: PhantomData<PG>,
 }
 
 impl <PG: PaddingGenerator> CBC <PG> {
 	pub fn new<U: Into<Block128>>(iv: U, key: U) -> CBC<PG> {
-		CBC {iv: iv.into(), block_cipher: Speck_128_128::new(key.into()), padd_generator: PhantomData::<PG> }
+		let iv_block: Block128 = iv.into();
+		let mut iv_bytes: [u8; block128::BYTES_IN_BLOCK] = [0; block128::BYTES_IN_BLOCK];
+		util::words_to_bytes_le(&[iv_block.get_a(), iv_block.get_b()], &mut iv_bytes);
+		CBC::with_cipher(&iv_bytes, Speck_128_128::new(key.into()))
 	}
 
 	pub fn cbc_encrypt_blocks(&self, plaintext: &[u64]) -> Vec<u64> {
 		assert!(!plaintext.is_empty(), "Input plaintext should not be empty!");
 		assert!(plaintext.len() % WORDS_IN_BLOCK == 0, "Input buffer has odd length {0}!", plaintext.len());
 
-		let mut ciphertext: Vec<u64> = Vec::with_capacity(plaintext.len()/BYTES_IN_WORD+2);
+		let iv: [u64; WORDS_IN_BLOCK] = self.iv_words();
+		let mut ciphertext: Vec<u64> = Vec::with_capacity(plaintext.len());
 
-		let (a, b) = self.block_cipher.speck_encrypt(plaintext[0] ^ self.iv.get_a(), plaintext[1] ^ self.iv.get_b());
+		let (a, b) = self.block_cipher.speck_encrypt(plaintext[0] ^ iv[0], plaintext[1] ^ iv[1]);
 		ciphertext.push(a);
 		ciphertext.push(b);
 
@@ -49,11 +66,12 @@ impl <PG: PaddingGenerator> CBC <PG> {
 		assert!(!ciphertext.is_empty(), "Input ciphertext should not be empty!");
 		assert!(ciphertext.len() % WORDS_IN_BLOCK == 0, "Input buffer has odd length {0}!", ciphertext.len());
 
-		let mut decryptedtext: Vec<u64> = Vec::with_capacity(ciphertext.len()/BYTES_IN_WORD+2);
+		let iv: [u64; WORDS_IN_BLOCK] = self.iv_words();
+		let mut decryptedtext: Vec<u64> = Vec::with_capacity(ciphertext.len());
 
 		let (a, b) = self.block_cipher.speck_decrypt(ciphertext[0], ciphertext[1]);
-		decryptedtext.push(a ^ self.iv.get_a());
-		decryptedtext.push(b ^ self.iv.get_b());
+		decryptedtext.push(a ^ iv[0]);
+		decryptedtext.push(b ^ iv[1]);
 
 //		TODO: Use step_by in future
 		for i in (2 .. ciphertext.len()).filter(|x| x % 2 == 0) {
@@ -65,66 +83,155 @@ impl <PG: PaddingGenerator> CBC <PG> {
 		decryptedtext
 	}
 
-	pub fn cbc_encrypt_byte_array(&self, plaintext: &[u8]) -> Result<Vec<u8>, CipherErrors> {
-		if (plaintext.is_empty()) { return Err(CipherErrors::WrongInput) };
+	fn iv_words(&self) -> [u64; WORDS_IN_BLOCK] {
+		let mut iv: [u64; WORDS_IN_BLOCK] = [0; WORDS_IN_BLOCK];
+		util::bytes_to_words_le(&self.iv, &mut iv);
+		iv
+	}
+}
 
-		let mut last_block: [u8; block128::BYTES_IN_BLOCK] = [0; block128::BYTES_IN_BLOCK];
-		PG::set_padding(plaintext, &mut last_block, block128::BYTES_IN_BLOCK);
+impl <PG: PaddingGenerator, BC: BlockCipher> CBC <PG, BC> {
+	pub fn with_cipher(iv: &[u8], block_cipher: BC) -> CBC<PG, BC> {
+		assert!(iv.len() == BC::BLOCK_SIZE, "IV must have {0} bytes!", BC::BLOCK_SIZE);
+		CBC {iv: iv.to_vec(), block_cipher, padd_generator: PhantomData::<PG> }
+	}
 
-		let last_block_u64: &[u64] = util::bytes_to_words(&last_block);
-		let plaintext_u64:  &[u64] = util::bytes_to_words(plaintext/*, BYTES_IN_WORD*/);
+	// Blocks are passed to the cipher as bytes, Speck_128_128 reads them as little-endian words.
+	// Before BlockCipher the plaintext words were read big-endian and the ciphertext written
+	// little-endian, so ciphertexts of the old byte API differ from the current ones.
+	// The old decryption did not invert the old encryption either, so there is nothing to keep.
+	pub fn cbc_encrypt_byte_array(&self, plaintext: &[u8]) -> Result<Vec<u8>, CipherErrors> {
+		if (plaintext.is_empty()) { return Err(CipherErrors::WrongInput) };
 
-		let mut ciphertext: Vec<u8> = Vec::with_capacity(plaintext.len() + last_block.len());
+		let mut last_block: Vec<u8> = vec![0; BC::BLOCK_SIZE];
+		PG::set_padding(plaintext, &mut last_block, BC::BLOCK_SIZE);
 
-		let (mut a, mut b) = self.block_cipher.speck_encrypt(
-			plaintext_u64[0].to_be() ^ self.iv.get_a(), plaintext_u64[1].to_be() ^ self.iv.get_b());
-		ciphertext.extend_from_slice(util::words_to_bytes(&[a, b]));
+		let full_blocks_len: usize = plaintext.len() - plaintext.len() % BC::BLOCK_SIZE;
+		let mut ciphertext: Vec<u8> = Vec::with_capacity(full_blocks_len + last_block.len());
+		ciphertext.extend_from_slice(&plaintext[..full_blocks_len]);
+		ciphertext.extend_from_slice(&last_block);
 
-//		TODO: Better way, but non-working right now
-//		for i in (2..plaintext.len()).step_by(2) {
-		for i in (2 .. plaintext_u64.len()).filter(|x| x % 2 == 0) {
-			let (c, d) = self.block_cipher.speck_encrypt(plaintext_u64[i].to_be() ^ a, plaintext_u64[i+1].to_be() ^ b);
-			ciphertext.extend_from_slice(util::words_to_bytes(&[c, d]));
-			a = c;
-			b = d;
+		let mut previous: Vec<u8> = self.iv.clone();
+		for block in ciphertext.chunks_mut(BC::BLOCK_SIZE) {
+			util::xor_in_place(block, &previous);
+			self.block_cipher.encrypt_block(block);
+			previous.copy_from_slice(block);
 		}
-		let (c, d) = self.block_cipher.speck_encrypt(last_block_u64[0].to_be() ^ a, last_block_u64[1].to_be() ^ b);
-		ciphertext.extend_from_slice(util::words_to_bytes(&[c, d]));
 
 		Ok(ciphertext)
 	}
 
 	pub fn cbc_decrypt_byte_array(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherErrors> {
-		if (ciphertext.is_empty() || ciphertext.len() % block128::BYTES_IN_BLOCK != 0) { return Err(CipherErrors::WrongInput) };
+		if (ciphertext.is_empty() || !ciphertext.len().is_multiple_of(BC::BLOCK_SIZE)) { return Err(CipherErrors::WrongInput) };
 
-		let ciphertext_u64: &[u64] = util::bytes_to_words(&ciphertext/*, BYTES_IN_WORD*/);
+		let mut decrypted: Vec<u8> = ciphertext.to_vec();
 
-		let mut decrypted: Vec<u8> = Vec::with_capacity(ciphertext.len());
-
-		let (mut a, mut b) = self.block_cipher.speck_decrypt(ciphertext_u64[0].to_be(), ciphertext_u64[1].to_be());
-		decrypted.extend_from_slice(util::words_to_bytes(&[a^self.iv.get_a(), b^self.iv.get_b()]));
-
-		for i in (2 .. ciphertext_u64.len()).filter(|x| x % 2 == 0) {
-			let (c, d) = self.block_cipher.speck_decrypt(ciphertext_u64[i].to_be(), ciphertext_u64[i+1].to_be() ^ b);
-			decrypted.extend_from_slice(util::words_to_bytes(&[c^a, d^b]));
-			a = c;
-			b = d;
+		let previous_blocks = iter::once(self.iv.as_slice()).chain(ciphertext.chunks(BC::BLOCK_SIZE));
+		for (block, previous) in decrypted.chunks_mut(BC::BLOCK_SIZE).zip(previous_blocks) {
+			self.block_cipher.decrypt_block(block);
+			util::xor_in_place(block, previous);
 		}
 
-		match PG::remove_padding(&decrypted, block128::BYTES_IN_BLOCK) {
+		match PG::remove_padding(&decrypted, BC::BLOCK_SIZE) {
 			Err(_)        => Err(CipherErrors::WrongPadding),
 			Ok(plaintext_len) => {
-				while (decrypted.len() > plaintext_len) {
-					decrypted.pop();
-				}
+				decrypted.truncate(plaintext_len);
 				Ok(decrypted)
 			},
 		}
 	}
-}
 
+	// No padding and no expansion: ciphertext has the same length as plaintext, which must be at least one block
+	pub fn cbc_cs_encrypt_byte_array(&self, plaintext: &[u8], variant: CiphertextStealing) -> Result<Vec<u8>, CipherErrors> {
+		let block_len: usize = BC::BLOCK_SIZE;
+		if (plaintext.len() < block_len) { return Err(CipherErrors::WrongInput) };
+
+		let last_len: usize = CBC::<PG, BC>::last_block_len(plaintext.len());
+		let head_len: usize = plaintext.len() - last_len;
 
+		//Usual CBC over zero-padded plaintext
+		let mut ciphertext: Vec<u8> = Vec::with_capacity(head_len + block_len);
+		ciphertext.extend_from_slice(plaintext);
+		ciphertext.resize(head_len + block_len, 0);
 
+		let mut previous: Vec<u8> = self.iv.clone();
+		for block in ciphertext.chunks_mut(block_len) {
+			util::xor_in_place(block, &previous);
+			self.block_cipher.encrypt_block(block);
+			previous.copy_from_slice(block);
+		}
+		if (head_len == 0) { return Ok(ciphertext) };
+
+		//Steal the tail of C[n-1]: it is recovered from C[n] during decryption
+		let last_block: Vec<u8> = ciphertext.split_off(head_len);
+		ciphertext.truncate(head_len - block_len + last_len);
+		if (CBC::<PG, BC>::blocks_swapped(variant, last_len)) {
+			let stolen: Vec<u8> = ciphertext.split_off(head_len - block_len);
+			ciphertext.extend_from_slice(&last_block);
+			ciphertext.extend_from_slice(&stolen);
+		} else {
+			ciphertext.extend_from_slice(&last_block);
+		}
+
+		Ok(ciphertext)
+	}
+
+	pub fn cbc_cs_decrypt_byte_array(&self, ciphertext: &[u8], variant: CiphertextStealing) -> Result<Vec<u8>, CipherErrors> {
+		let block_len: usize = BC::BLOCK_SIZE;
+		if (ciphertext.len() < block_len) { return Err(CipherErrors::WrongInput) };
+
+		let last_len: usize = CBC::<PG, BC>::last_block_len(ciphertext.len());
+		let prefix_len: usize = match ciphertext.len() == block_len {
+			true  => block_len,
+			false => ciphertext.len() - last_len - block_len,
+		};
+
+		//C[1] .. C[n-2] are decrypted as usual
+		let mut decrypted: Vec<u8> = ciphertext[..prefix_len].to_vec();
+		let previous_blocks = iter::once(self.iv.as_slice()).chain(ciphertext.chunks(block_len));
+		for (block, previous) in decrypted.chunks_mut(block_len).zip(previous_blocks) {
+			self.block_cipher.decrypt_block(block);
+			util::xor_in_place(block, previous);
+		}
+		if (ciphertext.len() == block_len) { return Ok(decrypted) };
+
+		let tail: &[u8] = &ciphertext[prefix_len..];
+		let (stolen, last_block): (&[u8], &[u8]) = match CBC::<PG, BC>::blocks_swapped(variant, last_len) {
+			true  => (&tail[block_len..], &tail[..block_len]),
+			false => (&tail[..last_len], &tail[last_len..]),
+		};
+		let previous: &[u8] = if (prefix_len == 0) { &self.iv } else { &ciphertext[prefix_len - block_len .. prefix_len] };
+
+		//D(C[n]) = C[n-1] ^ P[n], and P[n] was padded with zeros, so the tail of it is the stolen part of C[n-1]
+		let mut last_decrypted: Vec<u8> = last_block.to_vec();
+		self.block_cipher.decrypt_block(&mut last_decrypted);
+		let mut penultimate: Vec<u8> = stolen.to_vec();
+		penultimate.extend_from_slice(&last_decrypted[last_len..]);
+		util::xor_in_place(&mut last_decrypted, &penultimate);
+
+		self.block_cipher.decrypt_block(&mut penultimate);
+		util::xor_in_place(&mut penultimate, previous);
+
+		decrypted.extend_from_slice(&penultimate);
+		decrypted.extend_from_slice(&last_decrypted[..last_len]);
+		Ok(decrypted)
+	}
+
+	fn last_block_len(text_len: usize) -> usize {
+		match text_len % BC::BLOCK_SIZE {
+			0 => BC::BLOCK_SIZE,
+			x => x,
+		}
+	}
+
+	fn blocks_swapped(variant: CiphertextStealing, last_len: usize) -> bool {
+		match variant {
+			CiphertextStealing::CS1 => false,
+			CiphertextStealing::CS2 => last_len != BC::BLOCK_SIZE,
+			CiphertextStealing::CS3 => true,
+		}
+	}
+}
 
 #[test]
 fn cbc_works1() {
@@ -155,3 +262,140 @@ fn cbc_works2() {
 	let decryptedtext2: Vec<u64> = c.cbc_decrypt_blocks(&ciphertext2);
 	assert_eq!(decryptedtext2, long_plaintext);
 }
+
+#[test]
+fn cbc_byte_array_works1() {
+	let plaintext      = [0xB6ECC96CEC3EE647, 0x0D698FDCED742594, 0x78BCB34D52D1B961, 0xA03EF56F828A60DE];
+	let key: [u64; 2]  = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let iv: [u64; 2]   = [0xD2C4B7D96C49160E, 0x4EFE0C3E3B9FFD85];
+
+	let c: CBC<PKCS7> = CBC::new(&iv, &key);
+
+	let mut plaintext_bytes: [u8; 32] = [0; 32];
+	util::words_to_bytes_le(&plaintext, &mut plaintext_bytes);
+	let ciphertext_words: Vec<u64> = c.cbc_encrypt_blocks(&plaintext);
+	let mut ciphertext_bytes: [u8; 32] = [0; 32];
+	util::words_to_bytes_le(&ciphertext_words, &mut ciphertext_bytes);
+
+	//Both APIs give the same blocks, the byte one appends a padding block
+	let ciphertext: Vec<u8> = c.cbc_encrypt_byte_array(&plaintext_bytes).unwrap();
+	assert_eq!(ciphertext.len(), 48);
+	assert_eq!(&ciphertext[..32], &ciphertext_bytes[..]);
+
+	let decrypted: Vec<u8> = c.cbc_decrypt_byte_array(&ciphertext).unwrap();
+	assert_eq!(decrypted, plaintext_bytes.to_vec());
+}
+
+#[test]
+fn cbc_byte_array_works2() {
+	let key: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f];
+	let iv: [u8; 8]   = [0xD2, 0xC4, 0xB7, 0xD9, 0x6C, 0x49, 0x16, 0x0E];
+	let plaintext: Vec<u8> = (0..45).collect();
+
+	let c1: CBC<PKCS7, Speck_64_128> = CBC::with_cipher(&iv, Speck_64_128::from_key_bytes(&key));
+	let c2: CBC<PKCS7, Simon_64_128> = CBC::with_cipher(&iv, Simon_64_128::from_key_bytes(&key));
+
+	for len in 1 .. plaintext.len() {
+		let ciphertext1: Vec<u8> = c1.cbc_encrypt_byte_array(&plaintext[..len]).unwrap();
+		let ciphertext2: Vec<u8> = c2.cbc_encrypt_byte_array(&plaintext[..len]).unwrap();
+		assert_eq!(ciphertext1.len(), (len / 8 + 1) * 8);
+		assert!(ciphertext1 != ciphertext2);
+
+		assert_eq!(c1.cbc_decrypt_byte_array(&ciphertext1).unwrap(), &plaintext[..len]);
+		assert_eq!(c2.cbc_decrypt_byte_array(&ciphertext2).unwrap(), &plaintext[..len]);
+	}
+}
+
+#[test]
+fn cbc_byte_array_works3() {
+	//Pins the byte order of the byte API, expected values are computed with an independent
+	//Speck128/128 implementation checked against the test vector of the Speck paper
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let iv: [u8; 16]  = [0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xab, 0xac, 0xad, 0xae, 0xaf];
+	let plaintext: Vec<u8> = (0x20..0x34).collect();
+	let expected: [u8; 32] = [
+		0x85, 0x86, 0xc1, 0x9f, 0xa7, 0x7a, 0x4d, 0x5a, 0xd9, 0x0a, 0x59, 0x8a, 0x4a, 0x49, 0xec, 0x40,
+		0x8c, 0x25, 0xe2, 0x67, 0xf8, 0x04, 0xe5, 0xd5, 0xa9, 0xf6, 0x4c, 0x2e, 0xae, 0x80, 0xc5, 0x9c];
+
+	let c: CBC<PKCS7> = CBC::with_cipher(&iv, Speck_128_128::new(&key));
+	let ciphertext: Vec<u8> = c.cbc_encrypt_byte_array(&plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.cbc_decrypt_byte_array(&ciphertext).unwrap(), plaintext);
+}
+
+#[test]
+fn cbc_byte_array_wrong_input() {
+	let key: [u8; 8] = [0x00, 0x01, 0x08, 0x09, 0x10, 0x11, 0x18, 0x19];
+	let iv: [u8; 4]  = [0xD2, 0xC4, 0xB7, 0xD9];
+
+	let c: CBC<PKCS7, Speck_32_64> = CBC::with_cipher(&iv, Speck_32_64::from_key_bytes(&key));
+
+	assert!(c.cbc_encrypt_byte_array(&[]).is_err());
+	assert!(c.cbc_decrypt_byte_array(&[]).is_err());
+	assert!(c.cbc_decrypt_byte_array(&[0x01, 0x02, 0x03, 0x04, 0x05, 0x06]).is_err());
+}
+
+#[test]
+fn cbc_cs_works1() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let iv: [u8; 16]  = [0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xab, 0xac, 0xad, 0xae, 0xaf];
+	let plaintext: Vec<u8> = (0x30 .. 0x58).collect();
+	let expected: [(usize, CiphertextStealing, Vec<u8>); 8] = [
+		(17, CiphertextStealing::CS1, vec![
+			0xf0, 0x33, 0x56, 0x9b, 0x7e, 0x58, 0x21, 0xc6, 0x0c, 0xb1, 0x1d, 0x21, 0x8c, 0xcd, 0xee, 0xfb,
+			0x81]),
+		(17, CiphertextStealing::CS3, vec![
+			0x33, 0x56, 0x9b, 0x7e, 0x58, 0x21, 0xc6, 0x0c, 0xb1, 0x1d, 0x21, 0x8c, 0xcd, 0xee, 0xfb, 0x81,
+			0xf0]),
+		(31, CiphertextStealing::CS1, vec![
+			0xf0, 0x93, 0xdd, 0xba, 0xc1, 0xc7, 0xaa, 0xa2, 0x83, 0xc3, 0x9d, 0xfe, 0x97, 0xbe, 0x92, 0x43,
+			0x66, 0x8e, 0x1d, 0x76, 0xe8, 0xec, 0xf8, 0x9a, 0x0e, 0x60, 0x89, 0x09, 0x92, 0x3b, 0x67]),
+		(31, CiphertextStealing::CS2, vec![
+			0x43, 0x66, 0x8e, 0x1d, 0x76, 0xe8, 0xec, 0xf8, 0x9a, 0x0e, 0x60, 0x89, 0x09, 0x92, 0x3b, 0x67,
+			0xf0, 0x93, 0xdd, 0xba, 0xc1, 0xc7, 0xaa, 0xa2, 0x83, 0xc3, 0x9d, 0xfe, 0x97, 0xbe, 0x92]),
+		(32, CiphertextStealing::CS2, vec![
+			0xf0, 0x93, 0xdd, 0xba, 0xc1, 0xc7, 0xaa, 0xa2, 0x83, 0xc3, 0x9d, 0xfe, 0x97, 0xbe, 0x92, 0x84,
+			0x03, 0x06, 0x4b, 0xf8, 0x9c, 0x71, 0x8d, 0x48, 0x23, 0xc6, 0x54, 0xa8, 0xc9, 0xe0, 0x62, 0x1e]),
+		(32, CiphertextStealing::CS3, vec![
+			0x03, 0x06, 0x4b, 0xf8, 0x9c, 0x71, 0x8d, 0x48, 0x23, 0xc6, 0x54, 0xa8, 0xc9, 0xe0, 0x62, 0x1e,
+			0xf0, 0x93, 0xdd, 0xba, 0xc1, 0xc7, 0xaa, 0xa2, 0x83, 0xc3, 0x9d, 0xfe, 0x97, 0xbe, 0x92, 0x84]),
+		(40, CiphertextStealing::CS1, vec![
+			0xf0, 0x93, 0xdd, 0xba, 0xc1, 0xc7, 0xaa, 0xa2, 0x83, 0xc3, 0x9d, 0xfe, 0x97, 0xbe, 0x92, 0x84,
+			0x03, 0x06, 0x4b, 0xf8, 0x9c, 0x71, 0x8d, 0x48, 0xf1, 0xd1, 0x16, 0x3d, 0x9c, 0xf0, 0x0c, 0x7a,
+			0xfb, 0x8f, 0x4d, 0xc7, 0x56, 0xb3, 0x8d, 0x5e]),
+		(40, CiphertextStealing::CS3, vec![
+			0xf0, 0x93, 0xdd, 0xba, 0xc1, 0xc7, 0xaa, 0xa2, 0x83, 0xc3, 0x9d, 0xfe, 0x97, 0xbe, 0x92, 0x84,
+			0xf1, 0xd1, 0x16, 0x3d, 0x9c, 0xf0, 0x0c, 0x7a, 0xfb, 0x8f, 0x4d, 0xc7, 0x56, 0xb3, 0x8d, 0x5e,
+			0x03, 0x06, 0x4b, 0xf8, 0x9c, 0x71, 0x8d, 0x48])];
+
+	let c: CBC<PKCS7> = CBC::with_cipher(&iv, Speck_128_128::new(&key));
+
+	for &(len, variant, ref ciphertext) in expected.iter() {
+		assert_eq!(&c.cbc_cs_encrypt_byte_array(&plaintext[..len], variant).unwrap(), ciphertext);
+		assert_eq!(c.cbc_cs_decrypt_byte_array(ciphertext, variant).unwrap(), &plaintext[..len]);
+	}
+}
+
+#[test]
+fn cbc_cs_works2() {
+	let key: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x08, 0x09, 0x0a, 0x0b, 0x10, 0x11, 0x12, 0x13, 0x18, 0x19, 0x1a, 0x1b];
+	let iv: [u8; 8]   = [0xD2, 0xC4, 0xB7, 0xD9, 0x6C, 0x49, 0x16, 0x0E];
+	let plaintext: Vec<u8> = (0..45).collect();
+
+	let c: CBC<PKCS7, Speck_64_128> = CBC::with_cipher(&iv, Speck_64_128::from_key_bytes(&key));
+
+	for &variant in &[CiphertextStealing::CS1, CiphertextStealing::CS2, CiphertextStealing::CS3] {
+		for len in 8 .. plaintext.len() {
+			let ciphertext: Vec<u8> = c.cbc_cs_encrypt_byte_array(&plaintext[..len], variant).unwrap();
+			assert_eq!(ciphertext.len(), len);
+			assert_eq!(c.cbc_cs_decrypt_byte_array(&ciphertext, variant).unwrap(), &plaintext[..len]);
+		}
+		assert!(c.cbc_cs_encrypt_byte_array(&plaintext[..7], variant).is_err());
+		assert!(c.cbc_cs_decrypt_byte_array(&plaintext[..7], variant).is_err());
+	}
+
+	//Without a partial block CS1 is plain CBC
+	let padded: Vec<u8> = c.cbc_encrypt_byte_array(&plaintext[..40]).unwrap();
+	let stolen: Vec<u8> = c.cbc_cs_encrypt_byte_array(&plaintext[..40], CiphertextStealing::CS1).unwrap();
+	assert_eq!(&padded[..40], stolen.as_slice());
+}
@@ -0,0 +1,311 @@
+#![allow(unused_parens)]
+
+// Counter with CBC-MAC (RFC 3610) for 128-bit block ciphers.
+// L is the size of the length field (2..8 bytes), so nonce has 15 - L bytes.
+// M is the tag length (4, 6, ..., 16 bytes). M = 0 is CCM* encryption only mode (IEEE 802.15.4),
+// where nothing is authenticated and associated data is not allowed.
+
+use block128::Block128;
+use block_cipher::BlockCipher;
+use speck_128_128::Speck_128_128;
+use util;
+
+const BLOCK_LEN: usize = 16;
+
+#[derive(Debug, PartialEq)]
+pub enum CcmError {
+	WrongNonce,
+	InputTooLong,
+	WrongInput,
+	AuthenticationFailed,
+}
+
+pub struct CCM <BC = Speck_128_128> {
+	block_cipher: BC,
+	length_field_len: usize,
+	tag_len: usize,
+}
+
+impl CCM {
+	pub fn new<U: Into<Block128>>(key: U, length_field_len: usize, tag_len: usize) -> CCM {
+		CCM::with_cipher(Speck_128_128::new(key.into()), length_field_len, tag_len)
+	}
+}
+
+impl <BC: BlockCipher> CCM <BC> {
+	pub fn with_cipher(block_cipher: BC, length_field_len: usize, tag_len: usize) -> CCM<BC> {
+		assert!(BC::BLOCK_SIZE == BLOCK_LEN, "CCM is defined for 128-bit blocks only!");
+		assert!((2..=8).contains(&length_field_len), "Length field must have 2..8 bytes!");
+		assert!(tag_len == 0 || ((4..=BLOCK_LEN).contains(&tag_len) && tag_len.is_multiple_of(2)), "Tag must have 0, 4, 6, ..., 16 bytes!");
+		CCM {block_cipher, length_field_len, tag_len}
+	}
+
+	pub fn nonce_len(&self) -> usize {
+		15 - self.length_field_len
+	}
+
+	pub fn ccm_encrypt_byte_array(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, CcmError> {
+		self.check_input(nonce, aad, plaintext.len())?;
+
+		let mut ciphertext: Vec<u8> = Vec::with_capacity(plaintext.len() + self.tag_len);
+		ciphertext.extend_from_slice(plaintext);
+		self.ctr(nonce, &mut ciphertext);
+
+		if (self.tag_len > 0) {
+			let tag: [u8; BLOCK_LEN] = self.tag(nonce, aad, plaintext);
+			ciphertext.extend_from_slice(&tag[..self.tag_len]);
+		}
+		Ok(ciphertext)
+	}
+
+	pub fn ccm_decrypt_byte_array(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, CcmError> {
+		if (ciphertext.len() < self.tag_len) { return Err(CcmError::WrongInput) };
+		let (encrypted, tag) = ciphertext.split_at(ciphertext.len() - self.tag_len);
+		self.check_input(nonce, aad, encrypted.len())?;
+
+		let mut decrypted: Vec<u8> = encrypted.to_vec();
+		self.ctr(nonce, &mut decrypted);
+
+		if (self.tag_len > 0) {
+			let expected_tag: [u8; BLOCK_LEN] = self.tag(nonce, aad, &decrypted);
+			if (!util::constant_time_eq(&expected_tag[..self.tag_len], tag)) {
+				util::wipe(&mut decrypted);
+				return Err(CcmError::AuthenticationFailed);
+			}
+		}
+		Ok(decrypted)
+	}
+
+	fn check_input(&self, nonce: &[u8], aad: &[u8], text_len: usize) -> Result<(), CcmError> {
+		if (nonce.len() != self.nonce_len()) { return Err(CcmError::WrongNonce) };
+		if (self.tag_len == 0 && !aad.is_empty()) { return Err(CcmError::WrongInput) };
+		if (self.length_field_len < 8 && text_len as u64 >= 1 << (8 * self.length_field_len)) { return Err(CcmError::InputTooLong) };
+		Ok(())
+	}
+
+	// Counter block: flags with L - 1, nonce and a big-endian counter in L bytes
+	fn counter_block(&self, nonce: &[u8], counter: u64) -> [u8; BLOCK_LEN] {
+		let mut block: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		block[0] = (self.length_field_len - 1) as u8;
+		block[1 .. 1 + nonce.len()].copy_from_slice(nonce);
+		self.write_length_field(&mut block, counter);
+		block
+	}
+
+	// Last L bytes of a block hold a big-endian value
+	fn write_length_field(&self, block: &mut [u8; BLOCK_LEN], value: u64) {
+		block[BLOCK_LEN - self.length_field_len ..].copy_from_slice(&value.to_be_bytes()[8 - self.length_field_len ..]);
+	}
+
+	// A_0 encrypts the tag, the message is encrypted starting from A_1
+	fn ctr(&self, nonce: &[u8], data: &mut [u8]) {
+		for (i, chunk) in data.chunks_mut(BLOCK_LEN).enumerate() {
+			let mut keystream: [u8; BLOCK_LEN] = self.counter_block(nonce, i as u64 + 1);
+			self.block_cipher.encrypt_block(&mut keystream);
+			util::xor_in_place(chunk, &keystream);
+		}
+	}
+
+	fn tag(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> [u8; BLOCK_LEN] {
+		let mut b0: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		b0[0] = (if aad.is_empty() { 0 } else { 0x40 }) | ((((self.tag_len - 2) / 2) as u8) << 3) | ((self.length_field_len - 1) as u8);
+		b0[1 .. 1 + nonce.len()].copy_from_slice(nonce);
+		self.write_length_field(&mut b0, plaintext.len() as u64);
+
+		let mut mac: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		self.cbc_mac(&mut mac, &b0);
+
+		if (!aad.is_empty()) {
+			let mut encoded: Vec<u8> = Vec::with_capacity(aad.len() + 10);
+			if (aad.len() < 0xFF00) {
+				encoded.extend_from_slice(&(aad.len() as u16).to_be_bytes());
+			} else if (aad.len() as u64 <= 0xFFFFFFFF) {
+				encoded.extend_from_slice(&[0xFF, 0xFE]);
+				encoded.extend_from_slice(&(aad.len() as u32).to_be_bytes());
+			} else {
+				encoded.extend_from_slice(&[0xFF, 0xFF]);
+				encoded.extend_from_slice(&(aad.len() as u64).to_be_bytes());
+			}
+			encoded.extend_from_slice(aad);
+			self.cbc_mac(&mut mac, &encoded);
+		}
+		self.cbc_mac(&mut mac, plaintext);
+
+		let mut s0: [u8; BLOCK_LEN] = self.counter_block(nonce, 0);
+		self.block_cipher.encrypt_block(&mut s0);
+		util::xor_in_place(&mut mac, &s0);
+		mac
+	}
+
+	// Data is padded with zeros up to a full block
+	fn cbc_mac(&self, mac: &mut [u8; BLOCK_LEN], data: &[u8]) {
+		for chunk in data.chunks(BLOCK_LEN) {
+			util::xor_in_place(mac, chunk);
+			self.block_cipher.encrypt_block(mac);
+		}
+	}
+}
+
+#[cfg(test)]
+const TEST_KEY: [u64; 2] = [0xc7c6c5c4c3c2c1c0, 0xcfcecdcccbcac9c8];
+
+#[test]
+fn ccm_works1() {
+	//Parameters of RFC 3610 packet vector #1: M = 8, L = 2
+	let nonce: [u8; 13] = [0x00, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+	let aad: Vec<u8> = (0x00..0x08).collect();
+	let plaintext: Vec<u8> = (0x08..0x1f).collect();
+	let expected: [u8; 31] = [
+		0x18, 0xa3, 0x63, 0xb4, 0x68, 0x42, 0xfb, 0xa3, 0x8a, 0xea, 0x58, 0xf0, 0x75, 0xc0, 0x7f, 0x56,
+		0x34, 0xfe, 0x54, 0x1b, 0xc0, 0x13, 0xb4, 0x96, 0xec, 0x73, 0xda, 0x7d, 0x97, 0x83, 0xc1];
+
+	let c: CCM = CCM::new(&TEST_KEY, 2, 8);
+	let ciphertext: Vec<u8> = c.ccm_encrypt_byte_array(&nonce, &aad, &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.ccm_decrypt_byte_array(&nonce, &aad, &ciphertext).unwrap(), plaintext);
+}
+
+#[test]
+fn ccm_works2() {
+	//Parameters of RFC 3610 packet vector #2: block-aligned payload
+	let nonce: [u8; 13] = [0x00, 0x00, 0x00, 0x04, 0x03, 0x02, 0x01, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+	let aad: Vec<u8> = (0x00..0x08).collect();
+	let plaintext: Vec<u8> = (0x08..0x20).collect();
+	let expected: [u8; 32] = [
+		0xc5, 0xf6, 0x40, 0xde, 0xbd, 0x69, 0x27, 0xc8, 0x3f, 0x17, 0x37, 0xb5, 0xa7, 0xe0, 0xf6, 0x8b,
+		0x20, 0x98, 0xd4, 0x62, 0x5d, 0xb6, 0x9b, 0xdb, 0xee, 0xf7, 0xe6, 0x97, 0x43, 0x65, 0xac, 0x42];
+
+	let c: CCM = CCM::new(&TEST_KEY, 2, 8);
+	let ciphertext: Vec<u8> = c.ccm_encrypt_byte_array(&nonce, &aad, &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.ccm_decrypt_byte_array(&nonce, &aad, &ciphertext).unwrap(), plaintext);
+}
+
+#[test]
+fn ccm_works3() {
+	//Parameters and data of RFC 3610 packet vector #13: M = 10, L = 2
+	let nonce: [u8; 13] = [0x00, 0x41, 0x2b, 0x4e, 0xa9, 0xcd, 0xbe, 0x3c, 0x96, 0x96, 0x76, 0x6c, 0xfa];
+	let aad: [u8; 8] = [0x0b, 0xe1, 0xa8, 0x8b, 0xac, 0xe0, 0x18, 0xb1];
+	let plaintext: [u8; 23] = [
+		0x08, 0xe8, 0xcf, 0x97, 0xd8, 0x20, 0xea, 0x25, 0x84, 0x60, 0xe9, 0x6a, 0xd9, 0xcf, 0x52, 0x89,
+		0x05, 0x4d, 0x89, 0x5c, 0xea, 0xc4, 0x7c];
+	let expected: [u8; 33] = [
+		0x80, 0x44, 0xc6, 0xbb, 0x70, 0xd6, 0x4e, 0xf1, 0x18, 0xbe, 0x87, 0x1f, 0xbc, 0xec, 0xaa, 0x00,
+		0x1c, 0xa2, 0x8a, 0xcb, 0x31, 0xd6, 0x2e, 0x89, 0xca, 0x10, 0x1f, 0x5a, 0x79, 0xb7, 0x25, 0x98,
+		0x26];
+
+	let c: CCM = CCM::new(&TEST_KEY, 2, 10);
+	let ciphertext: Vec<u8> = c.ccm_encrypt_byte_array(&nonce, &aad, &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.ccm_decrypt_byte_array(&nonce, &aad, &ciphertext).unwrap().as_slice(), &plaintext[..]);
+}
+
+#[test]
+fn ccm_length_field_sizes() {
+	//L = 3 with 12-byte nonce, no associated data, M = 16
+	let nonce: Vec<u8> = (0x10..0x1c).collect();
+	let plaintext: Vec<u8> = (0..40).collect();
+	let expected: [u8; 56] = [
+		0xb6, 0x1b, 0x91, 0xcb, 0x56, 0xed, 0x0f, 0xf2, 0x8d, 0xc3, 0x2e, 0xba, 0xea, 0x1a, 0xae, 0xd6,
+		0xad, 0x9f, 0xa3, 0x90, 0x58, 0xed, 0x8d, 0xda, 0xb0, 0x85, 0x82, 0x22, 0x91, 0x4c, 0xe3, 0x4a,
+		0xfd, 0x53, 0x3c, 0x9b, 0x72, 0xd9, 0xdc, 0xe4, 0x04, 0x51, 0xd6, 0xf0, 0x75, 0xd8, 0x27, 0x7e,
+		0x9d, 0x3c, 0x4b, 0x17, 0x90, 0x23, 0xe7, 0x01];
+
+	let c: CCM = CCM::new(&TEST_KEY, 3, 16);
+	assert_eq!(c.nonce_len(), 12);
+	let ciphertext: Vec<u8> = c.ccm_encrypt_byte_array(&nonce, &[], &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.ccm_decrypt_byte_array(&nonce, &[], &ciphertext).unwrap(), plaintext);
+
+	//L = 8 with 7-byte nonce, M = 4
+	let nonce: Vec<u8> = (0x10..0x17).collect();
+	let plaintext: Vec<u8> = (0..17).collect();
+	let expected: [u8; 21] = [
+		0x6f, 0x66, 0xfb, 0x57, 0xf9, 0x6f, 0xeb, 0x6a, 0xa1, 0xea, 0x49, 0x5e, 0x1a, 0xb9, 0x9d, 0x52,
+		0x77, 0x39, 0x7b, 0xfe, 0x5d];
+
+	let c: CCM = CCM::new(&TEST_KEY, 8, 4);
+	assert_eq!(c.nonce_len(), 7);
+	let ciphertext: Vec<u8> = c.ccm_encrypt_byte_array(&nonce, b"abc", &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.ccm_decrypt_byte_array(&nonce, b"abc", &ciphertext).unwrap(), plaintext);
+}
+
+#[test]
+fn ccm_long_aad() {
+	//Associated data of 0xFF00 bytes needs 6-byte length encoding
+	let nonce: [u8; 13] = [0x00, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+	let aad: Vec<u8> = (0 .. 0xFF00).map(|x| x as u8).collect();
+	let expected: [u8; 16] = [
+		0x58, 0x19, 0x99, 0x12, 0x4f, 0x6c, 0xe1, 0xcb, 0xeb, 0xf8, 0x02, 0xe3, 0x6c, 0xde, 0x66, 0xbd];
+
+	let c: CCM = CCM::new(&TEST_KEY, 2, 16);
+	let tag: Vec<u8> = c.ccm_encrypt_byte_array(&nonce, &aad, &[]).unwrap();
+	assert_eq!(tag.as_slice(), &expected[..]);
+	assert_eq!(c.ccm_decrypt_byte_array(&nonce, &aad, &tag).unwrap(), vec![]);
+}
+
+#[test]
+fn ccm_star_encryption_only() {
+	//M = 0: the same keystream as CCM, but no tag
+	let nonce: [u8; 13] = [0x00, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+	let plaintext: Vec<u8> = (0x08..0x1f).collect();
+	let expected: [u8; 23] = [
+		0x18, 0xa3, 0x63, 0xb4, 0x68, 0x42, 0xfb, 0xa3, 0x8a, 0xea, 0x58, 0xf0, 0x75, 0xc0, 0x7f, 0x56,
+		0x34, 0xfe, 0x54, 0x1b, 0xc0, 0x13, 0xb4];
+
+	let c: CCM = CCM::new(&TEST_KEY, 2, 0);
+	let ciphertext: Vec<u8> = c.ccm_encrypt_byte_array(&nonce, &[], &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.ccm_decrypt_byte_array(&nonce, &[], &ciphertext).unwrap(), plaintext);
+	assert_eq!(c.ccm_encrypt_byte_array(&nonce, b"header", &plaintext), Err(CcmError::WrongInput));
+}
+
+#[test]
+fn ccm_errors() {
+	let nonce: [u8; 13] = [0x00, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+	let plaintext: Vec<u8> = (0..30).collect();
+
+	let c: CCM = CCM::new(&TEST_KEY, 2, 8);
+	let ciphertext: Vec<u8> = c.ccm_encrypt_byte_array(&nonce, b"header", &plaintext).unwrap();
+	util::assert_bit_flips_detected(&ciphertext, |x| c.ccm_decrypt_byte_array(&nonce, b"header", x), CcmError::AuthenticationFailed);
+	assert_eq!(c.ccm_decrypt_byte_array(&nonce, b"Header", &ciphertext), Err(CcmError::AuthenticationFailed));
+	assert_eq!(c.ccm_decrypt_byte_array(&nonce, b"header", &ciphertext[..7]), Err(CcmError::WrongInput));
+	assert_eq!(c.ccm_encrypt_byte_array(&nonce[..12], b"header", &plaintext), Err(CcmError::WrongNonce));
+
+	//Length field of 2 bytes limits the message to 65535 bytes
+	assert_eq!(c.ccm_encrypt_byte_array(&nonce, &[], &vec![0; 0x10000]), Err(CcmError::InputTooLong));
+}
+
+#[test]
+fn ccm_length_fields_authenticated() {
+	let nonce: [u8; 13] = [0x00, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+	let plaintext: Vec<u8> = (0..30).collect();
+
+	let c: CCM = CCM::new(&TEST_KEY, 2, 16);
+	let ciphertext: Vec<u8> = c.ccm_encrypt_byte_array(&nonce, b"header", &plaintext).unwrap();
+
+	//M is in the flags of B_0, so a cut 16-byte tag is not a valid 8-byte one
+	let c8: CCM = CCM::new(&TEST_KEY, 2, 8);
+	assert_eq!(c8.ccm_decrypt_byte_array(&nonce, b"header", &ciphertext[..38]), Err(CcmError::AuthenticationFailed));
+
+	//The message length is in B_0 too, a dropped ciphertext byte fails with an untouched tag
+	let mut shortened: Vec<u8> = ciphertext[..29].to_vec();
+	shortened.extend_from_slice(&ciphertext[30..]);
+	assert_eq!(c.ccm_decrypt_byte_array(&nonce, b"header", &shortened), Err(CcmError::AuthenticationFailed));
+
+	//L = 3 takes a nonce one byte shorter, and L itself is authenticated
+	let c3: CCM = CCM::new(&TEST_KEY, 3, 16);
+	assert_eq!(c3.ccm_decrypt_byte_array(&nonce, b"header", &ciphertext), Err(CcmError::WrongNonce));
+	assert_eq!(c3.ccm_decrypt_byte_array(&nonce[..12], b"header", &ciphertext), Err(CcmError::AuthenticationFailed));
+
+	//The longest message a 2-byte length field can hold
+	let longest: Vec<u8> = c8.ccm_encrypt_byte_array(&nonce, &[], &vec![0; 0xFFFF]).unwrap();
+	assert_eq!(c8.ccm_decrypt_byte_array(&nonce, &[], &longest).unwrap().len(), 0xFFFF);
+}
+
+#[test]
+#[should_panic]
+fn ccm_odd_tag_len() {
+	let _c: CCM = CCM::new(&TEST_KEY, 2, 7);
+}
@@ -0,0 +1,141 @@
+#![allow(unused_parens)]
+
+use block128::Block128;
+use block_cipher::BlockCipher;
+use speck_128_128::Speck_128_128;
+#[cfg(test)]
+use speck::Speck_64_128;
+
+// Cipher feedback with segments of segment_bits (8 for CFB-8, 64 for CFB-64, 128 for CFB-128).
+// Ciphertext segments are shifted into the register. No padding is needed,
+// data can be passed in pieces of any size: an incomplete segment is continued on the next call.
+pub struct CFB <BC = Speck_128_128> {
+	register: Vec<u8>,
+	keystream: Vec<u8>,
+	segment: Vec<u8>,
+	segment_len: usize,
+	block_cipher: BC,
+}
+
+impl CFB {
+	pub fn new<U: Into<Block128>>(iv: &[u8], key: U, segment_bits: usize) -> CFB {
+		CFB::with_cipher(iv, Speck_128_128::new(key.into()), segment_bits)
+	}
+}
+
+impl <BC: BlockCipher> CFB <BC> {
+	pub fn with_cipher(iv: &[u8], block_cipher: BC, segment_bits: usize) -> CFB<BC> {
+		assert!(iv.len() == BC::BLOCK_SIZE, "IV must have {0} bytes!", BC::BLOCK_SIZE);
+		assert!(segment_bits > 0 && segment_bits.is_multiple_of(8) && segment_bits <= BC::BLOCK_SIZE * 8,
+			"Segment must have whole number of bytes, but not more than a block!");
+		let segment_len: usize = segment_bits / 8;
+		CFB {register: iv.to_vec(), keystream: vec![0; BC::BLOCK_SIZE], segment: Vec::with_capacity(segment_len),
+			segment_len, block_cipher}
+	}
+
+	pub fn encrypt(&mut self, data: &mut [u8]) {
+		for x in data.iter_mut() {
+			*x ^= self.next_keystream_byte();
+			self.push_ciphertext(*x);
+		}
+	}
+
+	pub fn decrypt(&mut self, data: &mut [u8]) {
+		for x in data.iter_mut() {
+			let ciphertext: u8 = *x;
+			*x ^= self.next_keystream_byte();
+			self.push_ciphertext(ciphertext);
+		}
+	}
+
+	pub fn cfb_encrypt_byte_array(&mut self, plaintext: &[u8]) -> Vec<u8> {
+		let mut ciphertext: Vec<u8> = plaintext.to_vec();
+		self.encrypt(&mut ciphertext);
+		ciphertext
+	}
+
+	pub fn cfb_decrypt_byte_array(&mut self, ciphertext: &[u8]) -> Vec<u8> {
+		let mut decrypted: Vec<u8> = ciphertext.to_vec();
+		self.decrypt(&mut decrypted);
+		decrypted
+	}
+
+	#[inline]
+	fn next_keystream_byte(&mut self) -> u8 {
+		if (self.segment.is_empty()) {
+			self.keystream.copy_from_slice(&self.register);
+			self.block_cipher.encrypt_block(&mut self.keystream);
+		}
+		self.keystream[self.segment.len()]
+	}
+
+	#[inline]
+	fn push_ciphertext(&mut self, ciphertext: u8) {
+		self.segment.push(ciphertext);
+		if (self.segment.len() == self.segment_len) {
+			let block_len: usize = self.register.len();
+			self.register.copy_within(self.segment_len .., 0);
+			self.register[block_len - self.segment_len ..].copy_from_slice(&self.segment);
+			self.segment.clear();
+		}
+	}
+}
+
+#[test]
+fn cfb_works1() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let iv: [u8; 16]  = [0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xab, 0xac, 0xad, 0xae, 0xaf];
+	let plaintext: [u8; 35] = [
+		0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x3b, 0x3c, 0x3d, 0x3e, 0x3f,
+		0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f,
+		0x50, 0x51, 0x52];
+	let expected_8: [u8; 35] = [
+		0x8c, 0xcd, 0xae, 0x67, 0xff, 0x1d, 0x7d, 0x5e, 0xb8, 0x09, 0xee, 0xec, 0x1b, 0x21, 0xca, 0x41,
+		0x92, 0x69, 0x7c, 0x49, 0x36, 0xf1, 0x50, 0x41, 0xb6, 0xcc, 0xff, 0x30, 0xbe, 0xc1, 0xd2, 0x88,
+		0x2a, 0x67, 0x06];
+	let expected_64: [u8; 35] = [
+		0x8c, 0x44, 0xf8, 0x63, 0xf5, 0x88, 0x74, 0x24, 0x2b, 0x39, 0xab, 0xe5, 0x92, 0x64, 0xf1, 0xdc,
+		0x12, 0x4a, 0xeb, 0xce, 0x1d, 0x6f, 0x62, 0x27, 0xdc, 0x2e, 0x9c, 0x98, 0xb6, 0x98, 0xec, 0x82,
+		0xd5, 0x6b, 0x80];
+	let expected_128: [u8; 35] = [
+		0x8c, 0x44, 0xf8, 0x63, 0xf5, 0x88, 0x74, 0x24, 0x9c, 0xc7, 0x06, 0x47, 0xf1, 0x8a, 0x22, 0xb8,
+		0xc2, 0x1d, 0xfc, 0x97, 0x0e, 0xcd, 0x17, 0x5b, 0x9b, 0xcc, 0xfc, 0xe7, 0x77, 0x2e, 0x85, 0x8b,
+		0xcc, 0xa3, 0x07];
+
+	for &(segment_bits, expected) in &[(8, expected_8), (64, expected_64), (128, expected_128)] {
+		let mut c: CFB = CFB::new(&iv, &key, segment_bits);
+		let ciphertext: Vec<u8> = c.cfb_encrypt_byte_array(&plaintext);
+		assert_eq!(ciphertext.as_slice(), &expected[..]);
+
+		let mut c2: CFB = CFB::new(&iv, &key, segment_bits);
+		let decrypted: Vec<u8> = c2.cfb_decrypt_byte_array(&ciphertext);
+		assert_eq!(decrypted.as_slice(), &plaintext[..]);
+	}
+}
+
+#[test]
+fn cfb_works2() {
+	let key: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x08, 0x09, 0x0a, 0x0b, 0x10, 0x11, 0x12, 0x13, 0x18, 0x19, 0x1a, 0x1b];
+	let iv: [u8; 8]   = [0xD2, 0xC4, 0xB7, 0xD9, 0x6C, 0x49, 0x16, 0x0E];
+	let plaintext: Vec<u8> = (0..100).collect();
+
+	for &segment_bits in &[8, 32, 64] {
+		let mut c1: CFB<Speck_64_128> = CFB::with_cipher(&iv, Speck_64_128::from_key_bytes(&key), segment_bits);
+		let ciphertext: Vec<u8> = c1.cfb_encrypt_byte_array(&plaintext);
+
+		//Pieces that don't match segments, as they come from a serial link
+		let mut c2: CFB<Speck_64_128> = CFB::with_cipher(&iv, Speck_64_128::from_key_bytes(&key), segment_bits);
+		let mut decrypted: Vec<u8> = ciphertext.clone();
+		for chunk in decrypted.chunks_mut(3) {
+			c2.decrypt(chunk);
+		}
+		assert_eq!(decrypted, plaintext);
+	}
+}
+
+#[test]
+#[should_panic]
+fn cfb_wrong_segment() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let _c: CFB = CFB::new(&[0; 16], &key, 12);
+}
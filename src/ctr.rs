@@ -0,0 +1,188 @@
+#![allow(unused_parens)]
+
+use block128::Block128;
+use block_cipher::{BlockCipher, CipherErrors};
+use speck_128_128::Speck_128_128;
+use util;
+use std::cmp;
+
+pub const NONCE_LEN: usize = 8;
+pub const COUNTER_LEN: usize = 8;
+
+// Counter block is the nonce followed by a big-endian counter.
+// Keystream can be consumed in pieces of any size and repositioned with seek.
+pub struct CTR <BC = Speck_128_128> {
+	iv: Vec<u8>,
+	counter_len: usize,
+	block_cipher: BC,
+	position: u64,
+}
+
+impl CTR {
+	// 64/64 split: 8 bytes of nonce, 8 bytes of counter starting from zero
+	pub fn new<U: Into<Block128>>(nonce: &[u8], key: U) -> CTR {
+		assert!(nonce.len() == NONCE_LEN, "Nonce must have {0} bytes!", NONCE_LEN);
+		let mut iv: [u8; NONCE_LEN + COUNTER_LEN] = [0; NONCE_LEN + COUNTER_LEN];
+		iv[..NONCE_LEN].copy_from_slice(nonce);
+		CTR::with_cipher(&iv, COUNTER_LEN, Speck_128_128::new(key.into()))
+	}
+}
+
+impl <BC: BlockCipher> CTR <BC> {
+	// iv is the first counter block, its last counter_len bytes are the initial counter value
+	pub fn with_cipher(iv: &[u8], counter_len: usize, block_cipher: BC) -> CTR<BC> {
+		assert!(iv.len() == BC::BLOCK_SIZE, "IV must have {0} bytes!", BC::BLOCK_SIZE);
+		assert!(counter_len > 0 && counter_len <= BC::BLOCK_SIZE, "Counter length must be in 1..{0}!", BC::BLOCK_SIZE);
+		CTR {iv: iv.to_vec(), counter_len, block_cipher, position: 0}
+	}
+
+	pub fn position(&self) -> u64 {
+		self.position
+	}
+
+	pub fn seek(&mut self, offset: u64) {
+		self.position = offset;
+	}
+
+	pub fn apply_keystream(&mut self, data: &mut [u8]) -> Result<(), CipherErrors> {
+		if (data.is_empty()) { return Ok(()) };
+
+		let block_len: u64 = BC::BLOCK_SIZE as u64;
+		let end: u64 = match self.position.checked_add(data.len() as u64) {
+			Some(end) => end,
+			None      => return Err(CipherErrors::CounterOverflow),
+		};
+		if (!self.counter_available((end - 1) / block_len)) { return Err(CipherErrors::CounterOverflow) };
+
+		let mut keystream: Vec<u8> = vec![0; BC::BLOCK_SIZE];
+		let mut done: usize = 0;
+		while (done < data.len()) {
+			let offset: usize = (self.position % block_len) as usize;
+			let chunk_len: usize = cmp::min(BC::BLOCK_SIZE - offset, data.len() - done);
+
+			self.counter_block(self.position / block_len, &mut keystream);
+			self.block_cipher.encrypt_block(&mut keystream);
+			util::xor_in_place(&mut data[done .. done + chunk_len], &keystream[offset .. offset + chunk_len]);
+
+			done += chunk_len;
+			self.position += chunk_len as u64;
+		}
+
+		Ok(())
+	}
+
+	pub fn ctr_encrypt_byte_array(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, CipherErrors> {
+		let mut ciphertext: Vec<u8> = plaintext.to_vec();
+		self.apply_keystream(&mut ciphertext)?;
+		Ok(ciphertext)
+	}
+
+	pub fn ctr_decrypt_byte_array(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherErrors> {
+		self.ctr_encrypt_byte_array(ciphertext)
+	}
+
+	fn initial_counter(&self) -> u128 {
+		self.iv[BC::BLOCK_SIZE - self.counter_len ..].iter().fold(0, |acc, &x| (acc << 8) | (x as u128))
+	}
+
+	// Counter must stay inside its field, otherwise the nonce part changes or keystream repeats
+	fn counter_available(&self, block_index: u64) -> bool {
+		match self.initial_counter().checked_add(block_index as u128) {
+			None          => false,
+			Some(counter) => self.counter_len >= 16 || counter < (1u128 << (8 * self.counter_len)),
+		}
+	}
+
+	fn counter_block(&self, block_index: u64, block: &mut [u8]) {
+		let counter: u128 = self.initial_counter() + block_index as u128;
+		block.copy_from_slice(&self.iv);
+		for (i, x) in block.iter_mut().rev().take(self.counter_len).enumerate() {
+			*x = (counter >> (8 * i)) as u8;
+		}
+	}
+}
+
+#[test]
+fn ctr_works1() {
+	//No published Speck CTR vectors exist, these are regression values of this implementation
+	let key: [u64; 2]  = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let nonce: [u8; 8] = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7];
+	let plaintext: [u8; 37] = [
+		0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x29, 0x2a, 0x2b, 0x2c, 0x2d, 0x2e, 0x2f,
+		0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x3b, 0x3c, 0x3d, 0x3e, 0x3f,
+		0x40, 0x41, 0x42, 0x43, 0x44];
+	let expected: [u8; 37] = [
+		0x79, 0x87, 0x45, 0x82, 0x9d, 0xa4, 0xe3, 0xa3, 0x0c, 0x67, 0x20, 0xf3, 0xd4, 0x36, 0xbe, 0xc3,
+		0x81, 0x66, 0xfc, 0x87, 0xc6, 0xfd, 0x9a, 0x42, 0xc6, 0x64, 0xc7, 0x63, 0x7d, 0x12, 0x44, 0xfa,
+		0x77, 0x72, 0x57, 0xde, 0xd0];
+
+	let mut c: CTR = CTR::new(&nonce, &key);
+	let ciphertext: Vec<u8> = c.ctr_encrypt_byte_array(&plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.position(), 37);
+
+	c.seek(0);
+	let decrypted: Vec<u8> = c.ctr_decrypt_byte_array(&ciphertext).unwrap();
+	assert_eq!(decrypted.as_slice(), &plaintext[..]);
+}
+
+#[test]
+fn ctr_works2() {
+	//The first keystream block is just an encrypted counter block
+	let key: [u64; 2]  = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let nonce: [u8; 8] = [0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+	let s: Speck_128_128 = Speck_128_128::new(&key);
+	let (a, b) = s.speck_encrypt(1, 0);
+	let mut expected: [u8; 16] = [0; 16];
+	util::words_to_bytes_le(&[a, b], &mut expected);
+
+	let mut c: CTR = CTR::new(&nonce, &key);
+	let mut keystream: [u8; 16] = [0; 16];
+	c.apply_keystream(&mut keystream).unwrap();
+	assert_eq!(keystream, expected);
+}
+
+#[test]
+fn ctr_chunks_and_seek() {
+	let key: [u64; 2]  = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let nonce: [u8; 8] = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7];
+	let plaintext: Vec<u8> = (0 .. 1000).map(|x| x as u8).collect();
+
+	let mut c: CTR = CTR::new(&nonce, &key);
+	let ciphertext: Vec<u8> = c.ctr_encrypt_byte_array(&plaintext).unwrap();
+
+	//Chunks of odd sizes give the same result
+	c.seek(0);
+	let mut chunked: Vec<u8> = plaintext.clone();
+	for chunk in chunked.chunks_mut(7) {
+		c.apply_keystream(chunk).unwrap();
+	}
+	assert_eq!(chunked, ciphertext);
+
+	//Random access
+	for &(from, to) in &[(333, 334), (15, 17), (512, 1000), (999, 1000), (0, 1)] {
+		c.seek(from as u64);
+		let decrypted: Vec<u8> = c.ctr_decrypt_byte_array(&ciphertext[from .. to]).unwrap();
+		assert_eq!(decrypted.as_slice(), &plaintext[from .. to]);
+		assert_eq!(c.position(), to as u64);
+	}
+}
+
+#[test]
+fn ctr_counter_overflow() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let mut iv: [u8; 16] = [0; 16];
+	iv[15] = 0xFE;
+
+	//One-byte counter: only two blocks are left
+	let mut c: CTR = CTR::with_cipher(&iv, 1, Speck_128_128::new(&key));
+	assert!(c.ctr_encrypt_byte_array(&[0; 32]).is_ok());
+	assert!(c.ctr_encrypt_byte_array(&[0; 1]).is_err());
+	c.seek(31);
+	assert!(c.ctr_encrypt_byte_array(&[0; 1]).is_ok());
+	assert!(c.ctr_encrypt_byte_array(&[0; 1]).is_err());
+
+	let mut c2: CTR = CTR::new(&[0; 8], &key);
+	c2.seek(!0);
+	assert!(c2.ctr_encrypt_byte_array(&[0; 2]).is_err());
+}
@@ -0,0 +1,155 @@
+#![allow(unused_parens)]
+
+// EAX mode (Bellare, Rogaway, Wagner): CTR for encryption and three OMACs for authentication.
+// No GF multiplication, nonce may have any length. Tag is appended to ciphertext.
+
+use block128::Block128;
+use block_cipher::BlockCipher;
+use speck_128_128::Speck_128_128;
+use mac::cmac::{OmacState, Subkeys};
+use util;
+
+const BLOCK_LEN: usize = 16;
+
+#[derive(Debug, PartialEq)]
+pub enum EaxError {
+	WrongInput,
+	AuthenticationFailed,
+}
+
+pub struct EAX <BC = Speck_128_128> {
+	block_cipher: BC,
+	subkeys: Subkeys,
+	tag_len: usize,
+}
+
+impl EAX {
+	pub fn new<U: Into<Block128>>(key: U, tag_len: usize) -> EAX {
+		EAX::with_cipher(Speck_128_128::new(key.into()), tag_len)
+	}
+}
+
+impl <BC: BlockCipher> EAX <BC> {
+	pub fn with_cipher(block_cipher: BC, tag_len: usize) -> EAX<BC> {
+		assert!(BC::BLOCK_SIZE == BLOCK_LEN, "EAX is defined for 128-bit blocks only!");
+		assert!(tag_len > 0 && tag_len <= BLOCK_LEN, "Tag must have 1..{0} bytes!", BLOCK_LEN);
+		let subkeys: Subkeys = Subkeys::new(&block_cipher);
+		EAX {block_cipher, subkeys, tag_len}
+	}
+
+	pub fn seal(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+		let n: [u8; BLOCK_LEN] = self.omac(0, nonce);
+		let mut ciphertext: Vec<u8> = Vec::with_capacity(plaintext.len() + self.tag_len);
+		ciphertext.extend_from_slice(plaintext);
+		self.ctr(&n, &mut ciphertext);
+
+		let tag: [u8; BLOCK_LEN] = self.tag(&n, aad, &ciphertext);
+		ciphertext.extend_from_slice(&tag[..self.tag_len]);
+		ciphertext
+	}
+
+	pub fn open(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, EaxError> {
+		if (ciphertext.len() < self.tag_len) { return Err(EaxError::WrongInput) };
+		let (encrypted, tag) = ciphertext.split_at(ciphertext.len() - self.tag_len);
+
+		let n: [u8; BLOCK_LEN] = self.omac(0, nonce);
+		let expected_tag: [u8; BLOCK_LEN] = self.tag(&n, aad, encrypted);
+		if (!util::constant_time_eq(&expected_tag[..self.tag_len], tag)) { return Err(EaxError::AuthenticationFailed) };
+
+		let mut decrypted: Vec<u8> = encrypted.to_vec();
+		self.ctr(&n, &mut decrypted);
+		Ok(decrypted)
+	}
+
+	fn omac(&self, tweak: u8, data: &[u8]) -> [u8; BLOCK_LEN] {
+		let mut omac: OmacState = OmacState::with_tweak(tweak);
+		omac.update(&self.block_cipher, data);
+		omac.finalize(&self.block_cipher, &self.subkeys)
+	}
+
+	fn tag(&self, n: &[u8; BLOCK_LEN], aad: &[u8], ciphertext: &[u8]) -> [u8; BLOCK_LEN] {
+		let mut tag: [u8; BLOCK_LEN] = *n;
+		util::xor_in_place(&mut tag, &self.omac(1, aad));
+		util::xor_in_place(&mut tag, &self.omac(2, ciphertext));
+		tag
+	}
+
+	// Counter is the whole block, incremented as a big-endian number
+	fn ctr(&self, n: &[u8; BLOCK_LEN], data: &mut [u8]) {
+		let mut counter: u128 = u128::from_be_bytes(*n);
+		for chunk in data.chunks_mut(BLOCK_LEN) {
+			let mut keystream: [u8; BLOCK_LEN] = counter.to_be_bytes();
+			self.block_cipher.encrypt_block(&mut keystream);
+			util::xor_in_place(chunk, &keystream);
+			counter = counter.wrapping_add(1);
+		}
+	}
+}
+
+#[test]
+fn eax_works1() {
+	//Nonces and headers of the first two test vectors from the EAX paper
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let nonce1: [u8; 16] = [0x62, 0xec, 0x67, 0xf9, 0xc3, 0xa4, 0xa4, 0x07, 0xfc, 0xb2, 0xa8, 0xc4, 0x90, 0x31, 0xa8, 0xb3];
+	let aad1: [u8; 8] = [0x6b, 0xfb, 0x91, 0x4f, 0xd0, 0x7e, 0xae, 0x6b];
+	let expected1: [u8; 16] = [0x8f, 0x7c, 0x9e, 0x77, 0x85, 0x83, 0xf1, 0xed, 0xe3, 0x86, 0x9e, 0xe8, 0x10, 0x72, 0x22, 0xe3];
+	let nonce2: [u8; 16] = [0xbe, 0xca, 0xf0, 0x43, 0xb0, 0xa2, 0x3d, 0x84, 0x31, 0x94, 0xba, 0x97, 0x2c, 0x66, 0xde, 0xbd];
+	let aad2: [u8; 8] = [0xfa, 0x3b, 0xfd, 0x48, 0x06, 0xeb, 0x53, 0xfa];
+	let expected2: [u8; 18] = [0xf2, 0x52, 0xd6, 0x0d, 0xca, 0xc9, 0xbd, 0x1a, 0xfa, 0xa4, 0xa8, 0x17, 0x7b, 0xab, 0x77, 0xf0, 0x2c, 0xc7];
+
+	let c: EAX = EAX::new(&key, 16);
+	let tag: Vec<u8> = c.seal(&nonce1, &aad1, &[]);
+	assert_eq!(tag.as_slice(), &expected1[..]);
+	assert_eq!(c.open(&nonce1, &aad1, &tag).unwrap(), vec![]);
+
+	let ciphertext: Vec<u8> = c.seal(&nonce2, &aad2, &[0xf7, 0xfb]);
+	assert_eq!(ciphertext.as_slice(), &expected2[..]);
+	assert_eq!(c.open(&nonce2, &aad2, &ciphertext).unwrap(), vec![0xf7, 0xfb]);
+}
+
+#[test]
+fn eax_works2() {
+	//Short nonce, no header, 64-bit tag
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let nonce: [u8; 4] = [0xca, 0xfe, 0xba, 0xbe];
+	let plaintext: Vec<u8> = (0..40).collect();
+	let expected: [u8; 48] = [
+		0x90, 0x50, 0x56, 0x0f, 0xcd, 0xca, 0x49, 0xe2, 0x20, 0x09, 0x0d, 0x9d, 0x5a, 0x0d, 0x80, 0xb3,
+		0xf2, 0xf2, 0xeb, 0xed, 0x9e, 0xbe, 0x97, 0xf1, 0xf7, 0x3b, 0xbe, 0x4c, 0xf4, 0x6f, 0x6b, 0xb4,
+		0x56, 0x10, 0xd3, 0xa1, 0x34, 0xe3, 0xcd, 0xb3, 0xa3, 0xc1, 0x3b, 0x5a, 0xad, 0x22, 0xb2, 0x8a];
+
+	let c: EAX = EAX::new(&key, 8);
+	let ciphertext: Vec<u8> = c.seal(&nonce, &[], &plaintext);
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.open(&nonce, &[], &ciphertext).unwrap(), plaintext);
+}
+
+#[test]
+fn eax_authentication_fails() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let nonce: [u8; 16] = [0x62, 0xec, 0x67, 0xf9, 0xc3, 0xa4, 0xa4, 0x07, 0xfc, 0xb2, 0xa8, 0xc4, 0x90, 0x31, 0xa8, 0xb3];
+	let plaintext: Vec<u8> = (0..33).collect();
+
+	let c: EAX = EAX::new(&key, 16);
+	let ciphertext: Vec<u8> = c.seal(&nonce, b"header", &plaintext);
+	util::assert_bit_flips_detected(&ciphertext, |x| c.open(&nonce, b"header", x), EaxError::AuthenticationFailed);
+	assert_eq!(c.open(&nonce, b"Header", &ciphertext), Err(EaxError::AuthenticationFailed));
+	assert_eq!(c.open(&nonce[1..], b"header", &ciphertext), Err(EaxError::AuthenticationFailed));
+	assert_eq!(c.open(&nonce, b"header", &ciphertext[..15]), Err(EaxError::WrongInput));
+}
+
+#[test]
+fn eax_nonce_and_header_separated() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let plaintext: Vec<u8> = (0..33).collect();
+
+	//Nonce and header are OMACed with different tweaks, so they can't be swapped
+	let c: EAX = EAX::new(&key, 16);
+	let ciphertext: Vec<u8> = c.seal(b"nonce", b"header", &plaintext);
+	assert_eq!(c.open(b"header", b"nonce", &ciphertext), Err(EaxError::AuthenticationFailed));
+
+	//An empty nonce is allowed, and it is authenticated like any other
+	let ciphertext: Vec<u8> = c.seal(&[], b"header", &plaintext);
+	assert_eq!(c.open(&[], b"header", &ciphertext).unwrap(), plaintext);
+	assert_eq!(c.open(&[0], b"header", &ciphertext), Err(EaxError::AuthenticationFailed));
+}
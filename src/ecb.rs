@@ -0,0 +1,103 @@
+#![allow(unused_parens)]
+
+// ECB leaks equal plaintext blocks, so it is only built with "hazmat" feature.
+// Use it for test vectors and interoperability with legacy devices, nothing else.
+
+use block128::Block128;
+use block_cipher::{BlockCipher, CipherErrors};
+use speck_128_128::Speck_128_128;
+use padding::PaddingGenerator;
+use std::marker::PhantomData;
+#[cfg(test)]
+use pkcs7::PKCS7;
+#[cfg(test)]
+use speck::Speck_32_64;
+
+pub struct ECB <PG, BC = Speck_128_128> {
+	block_cipher: BC,
+	padd_generator: PhantomData<PG>,
+}
+
+impl <PG: PaddingGenerator> ECB <PG> {
+	pub fn new<U: Into<Block128>>(key: U) -> ECB<PG> {
+		ECB::with_cipher(Speck_128_128::new(key.into()))
+	}
+}
+
+impl <PG: PaddingGenerator, BC: BlockCipher> ECB <PG, BC> {
+	pub fn with_cipher(block_cipher: BC) -> ECB<PG, BC> {
+		ECB {block_cipher, padd_generator: PhantomData::<PG> }
+	}
+
+	pub fn ecb_encrypt_byte_array(&self, plaintext: &[u8]) -> Result<Vec<u8>, CipherErrors> {
+		if (plaintext.is_empty()) { return Err(CipherErrors::WrongInput) };
+
+		let mut last_block: Vec<u8> = vec![0; BC::BLOCK_SIZE];
+		PG::set_padding(plaintext, &mut last_block, BC::BLOCK_SIZE);
+
+		let full_blocks_len: usize = plaintext.len() - plaintext.len() % BC::BLOCK_SIZE;
+		let mut ciphertext: Vec<u8> = Vec::with_capacity(full_blocks_len + last_block.len());
+		ciphertext.extend_from_slice(&plaintext[..full_blocks_len]);
+		ciphertext.extend_from_slice(&last_block);
+
+		for block in ciphertext.chunks_mut(BC::BLOCK_SIZE) {
+			self.block_cipher.encrypt_block(block);
+		}
+
+		Ok(ciphertext)
+	}
+
+	pub fn ecb_decrypt_byte_array(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherErrors> {
+		if (ciphertext.is_empty() || !ciphertext.len().is_multiple_of(BC::BLOCK_SIZE)) { return Err(CipherErrors::WrongInput) };
+
+		let mut decrypted: Vec<u8> = ciphertext.to_vec();
+		for block in decrypted.chunks_mut(BC::BLOCK_SIZE) {
+			self.block_cipher.decrypt_block(block);
+		}
+
+		match PG::remove_padding(&decrypted, BC::BLOCK_SIZE) {
+			Err(_)        => Err(CipherErrors::WrongPadding),
+			Ok(plaintext_len) => {
+				decrypted.truncate(plaintext_len);
+				Ok(decrypted)
+			},
+		}
+	}
+}
+
+#[test]
+fn ecb_works1() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let plaintext: [u8; 32] = [
+		0x20, 0x6d, 0x61, 0x64, 0x65, 0x20, 0x69, 0x74, 0x20, 0x65, 0x71, 0x75, 0x69, 0x76, 0x61, 0x6c,
+		0x20, 0x6d, 0x61, 0x64, 0x65, 0x20, 0x69, 0x74, 0x20, 0x65, 0x71, 0x75, 0x69, 0x76, 0x61, 0x6c];
+	let expected_block: [u8; 16] = [0x18, 0x0d, 0x57, 0x5c, 0xdf, 0xfe, 0x60, 0x78, 0x65, 0x32, 0x78, 0x79, 0x51, 0x98, 0x5d, 0xa6];
+
+	let e: ECB<PKCS7> = ECB::new(&key);
+
+	//Equal blocks give equal ciphertext, that's why ECB is hazardous
+	let ciphertext: Vec<u8> = e.ecb_encrypt_byte_array(&plaintext).unwrap();
+	assert_eq!(ciphertext.len(), 48);
+	assert_eq!(&ciphertext[0..16], &expected_block[..]);
+	assert_eq!(&ciphertext[16..32], &expected_block[..]);
+
+	let decrypted: Vec<u8> = e.ecb_decrypt_byte_array(&ciphertext).unwrap();
+	assert_eq!(decrypted.as_slice(), &plaintext[..]);
+}
+
+#[test]
+fn ecb_works2() {
+	let key: [u8; 8] = [0x00, 0x01, 0x08, 0x09, 0x10, 0x11, 0x18, 0x19];
+	let plaintext: Vec<u8> = (0..23).collect();
+
+	let e: ECB<PKCS7, Speck_32_64> = ECB::with_cipher(Speck_32_64::from_key_bytes(&key));
+
+	for len in 1 .. plaintext.len() {
+		let ciphertext: Vec<u8> = e.ecb_encrypt_byte_array(&plaintext[..len]).unwrap();
+		assert_eq!(ciphertext.len(), (len / 4 + 1) * 4);
+		assert_eq!(e.ecb_decrypt_byte_array(&ciphertext).unwrap(), &plaintext[..len]);
+	}
+
+	assert!(e.ecb_encrypt_byte_array(&[]).is_err());
+	assert!(e.ecb_decrypt_byte_array(&[0x01, 0x02, 0x03]).is_err());
+}
@@ -0,0 +1,120 @@
+// 128-EEA3 confidentiality algorithm of 3GPP (LTE), based on ZUC.
+// Ciphertext is the message XORed with the keystream, bits past the length are zeroed.
+
+use zuc_128::Zuc_128;
+
+// IV from the 32-bit COUNT, 5-bit BEARER and 1-bit DIRECTION
+fn eea3_iv(count: u32, bearer: u8, direction: u8) -> [u8; 16] {
+	let mut iv: [u8; 16] = [0; 16];
+	iv[..4].copy_from_slice(&count.to_be_bytes());
+	iv[4] = (bearer << 3) | (direction << 2);
+	let (head, tail) = iv.split_at_mut(8);
+	tail.copy_from_slice(head);
+	iv
+}
+
+// length is the message length in bits, the result has length.div_ceil(8) bytes.
+// Encryption and decryption are the same operation.
+pub fn eea3(key: &[u8; 16], count: u32, bearer: u8, direction: u8, length: usize, message: &[u8]) -> Vec<u8> {
+	assert!(bearer < 32, "Bearer must have 5 bits!");
+	assert!(direction < 2, "Direction must have 1 bit!");
+	assert!(message.len() * 8 >= length, "Message is shorter than {0} bits!", length);
+
+	let bytes_count: usize = length.div_ceil(8);
+	let words_count: usize = length.div_ceil(32);
+	let mut keystream: Vec<u32> = vec![0; words_count];
+	if words_count > 0 {
+		let mut zuc: Zuc_128 = Zuc_128::new(key, &eea3_iv(count, bearer, direction));
+		zuc.generate_keystream(&mut keystream, words_count);
+	}
+
+	let mut output: Vec<u8> = message[..bytes_count].iter()
+		.zip(keystream.iter().flat_map(|w| w.to_be_bytes()))
+		.map(|(m, k)| m ^ k)
+		.collect();
+
+	let last_bits: usize = length % 8;
+	if last_bits > 0 {
+		output[bytes_count - 1] &= 0xff << (8 - last_bits);
+	}
+	output
+}
+
+#[cfg(test)]
+fn words_to_bytes(words: &[u32]) -> Vec<u8> {
+	words.iter().flat_map(|w| w.to_be_bytes().to_vec()).collect()
+}
+
+// Specification of the 3GPP Confidentiality and Integrity
+// Algorithms 128-EEA3 & 128-EIA3
+// Document 3: Implementor’s Test Data
+#[test]
+fn eea3_test_set_1() {
+	let key: [u8; 16] = [0x17, 0x3d, 0x14, 0xba, 0x50, 0x03, 0x73, 0x1d,
+	                     0x7a, 0x60, 0x04, 0x94, 0x70, 0xf0, 0x0a, 0x29];
+	let plaintext: Vec<u8> = words_to_bytes(&[0x6cf65340, 0x735552ab, 0x0c9752fa, 0x6f9025fe,
+	                                          0x0bd675d9, 0x005875b2, 0x00000000]);
+	let ciphertext: Vec<u8> = words_to_bytes(&[0xa6c85fc6, 0x6afb8533, 0xaafc2518, 0xdfe78494,
+	                                           0x0ee1e4b0, 0x30238cc8, 0x00000000]);
+
+	let encrypted: Vec<u8> = eea3(&key, 0x66035492, 0x0f, 0, 193, &plaintext);
+	assert_eq!(encrypted.as_slice(), &ciphertext[..25]);
+	let decrypted: Vec<u8> = eea3(&key, 0x66035492, 0x0f, 0, 193, &encrypted);
+	assert_eq!(decrypted.as_slice(), &plaintext[..25]);
+
+	//Bits past the length do not leak into the output
+	let mut dirty: Vec<u8> = plaintext.clone();
+	dirty[24] = 0x7f;
+	assert_eq!(eea3(&key, 0x66035492, 0x0f, 0, 193, &dirty), encrypted);
+}
+
+#[test]
+fn eea3_test_set_2() {
+	let key: [u8; 16] = [0xe5, 0xbd, 0x3e, 0xa0, 0xeb, 0x55, 0xad, 0xe8,
+	                     0x66, 0xc6, 0xac, 0x58, 0xbd, 0x54, 0x30, 0x2a];
+	let plaintext: Vec<u8> = words_to_bytes(&[
+		0x14a8ef69, 0x3d678507, 0xbbe7270a, 0x7f67ff50, 0x06c3525b, 0x9807e467, 0xc4e56000, 0xba338f5d,
+		0x42955903, 0x67518222, 0x46c80d3b, 0x38f07f4b, 0xe2d8ff58, 0x05f51322, 0x29bde93b, 0xbbdcaf38,
+		0x2bf1ee97, 0x2fbf9977, 0xbada8945, 0x847a2a6c, 0x9ad34a66, 0x7554e04d, 0x1f7fa2c3, 0x3241bd8f,
+		0x01ba220d]);
+	let ciphertext: Vec<u8> = words_to_bytes(&[
+		0x131d43e0, 0xdea1be5c, 0x5a1bfd97, 0x1d852cbf, 0x712d7b4f, 0x57961fea, 0x3208afa8, 0xbca433f4,
+		0x56ad09c7, 0x417e58bc, 0x69cf8866, 0xd1353f74, 0x865e8078, 0x1d202dfb, 0x3ecff7fc, 0xbc3b190f,
+		0xe82a204e, 0xd0e350fc, 0x0f6f2613, 0xb2f2bca6, 0xdf5a473a, 0x57a4a00d, 0x985ebad8, 0x80d6f238,
+		0x64a07b01]);
+
+	assert_eq!(eea3(&key, 0x00056823, 0x18, 1, 800, &plaintext), ciphertext);
+	assert_eq!(eea3(&key, 0x00056823, 0x18, 1, 800, &ciphertext), plaintext);
+}
+
+#[test]
+fn eea3_test_set_3() {
+	let key: [u8; 16] = [0xd4, 0x55, 0x2a, 0x8f, 0xd6, 0xe6, 0x1c, 0xc8,
+	                     0x1a, 0x20, 0x09, 0x14, 0x1a, 0x29, 0xc1, 0x0b];
+	let plaintext: Vec<u8> = words_to_bytes(&[
+		0x38f07f4b, 0xe2d8ff58, 0x05f51322, 0x29bde93b, 0xbbdcaf38, 0x2bf1ee97, 0x2fbf9977, 0xbada8945,
+		0x847a2a6c, 0x9ad34a66, 0x7554e04d, 0x1f7fa2c3, 0x3241bd8f, 0x01ba220d, 0x3ca4ec41, 0xe074595f,
+		0x54ae2b45, 0x4fd97143, 0x20436019, 0x65cca85c, 0x2417ed6c, 0xbec3bada, 0x84fc8a57, 0x9aea7837,
+		0xb0271177, 0x242a64dc, 0x0a9de71a, 0x8edee86c, 0xa3d47d03, 0x3d6bf539, 0x804eca86, 0xc584a905,
+		0x2de46ad3, 0xfced6554, 0x3bd90207, 0x372b27af, 0xb79234f5, 0xff43ea87, 0x0820e2c2, 0xb78a8aae,
+		0x61cce52a, 0x0515e348, 0xd196664a, 0x3456b182, 0xa07c406e, 0x4a207912, 0x71cfeda1, 0x65d535ec,
+		0x5ea2d4df, 0x40000000]);
+	let ciphertext: Vec<u8> = words_to_bytes(&[
+		0x8383b022, 0x9fcc0b9d, 0x2295ec41, 0xc977e9c2, 0xbb72e220, 0x378141f9, 0xc8318f3a, 0x270dfbcd,
+		0xee6411c2, 0xb3044f17, 0x6dc6e00f, 0x8960f97a, 0xfacd131a, 0xd6a3b49b, 0x16b7babc, 0xf2a509eb,
+		0xb16a75dc, 0xab14ff27, 0x5dbeeea1, 0xa2b155f9, 0xd52c2645, 0x2d0187c3, 0x10a4ee55, 0xbeaa78ab,
+		0x4024615b, 0xa9f5d5ad, 0xc7728f73, 0x560671f0, 0x13e5e550, 0x085d3291, 0xdf7d5fec, 0xedded559,
+		0x641b6c2f, 0x585233bc, 0x71e9602b, 0xd2305855, 0xbbd25ffa, 0x7f17ecbc, 0x042daae3, 0x8c1f57ad,
+		0x8e8ebd37, 0x346f71be, 0xfdbb7432, 0xe0e0bb2c, 0xfc09bcd9, 0x6570cb0c, 0x0c39df5e, 0x29294e82,
+		0x703a637f, 0x80000000]);
+
+	let encrypted: Vec<u8> = eea3(&key, 0x76452ec1, 0x02, 1, 1570, &plaintext);
+	assert_eq!(encrypted.as_slice(), &ciphertext[..197]);
+	assert_eq!(eea3(&key, 0x76452ec1, 0x02, 1, 1570, &ciphertext).as_slice(), &plaintext[..197]);
+}
+
+#[test]
+fn eea3_empty_message() {
+	let key: [u8; 16] = [0; 16];
+	assert!(eea3(&key, 0, 0, 0, 0, &[]).is_empty());
+}
@@ -0,0 +1,300 @@
+#![allow(unused_parens)]
+
+// Galois/Counter Mode (NIST SP 800-38D) for 128-bit block ciphers.
+// Ciphertext is returned with the tag appended, tag can be truncated to 4, 8 or 12..16 bytes.
+// Tag is checked before any plaintext is produced.
+
+use block128::Block128;
+use block_cipher::BlockCipher;
+use speck_128_128::Speck_128_128;
+use util;
+
+const BLOCK_LEN: usize = 16;
+const R: u128 = 0xE1 << 120;
+// 2^39 - 256 bits
+const MAX_PLAINTEXT_LEN: u64 = (1 << 36) - 32;
+
+#[derive(Debug, PartialEq)]
+pub enum GcmError {
+	EmptyNonce,
+	InputTooLong,
+	WrongInput,
+	AuthenticationFailed,
+}
+
+// Bits of a block are numbered from the most significant bit of the first byte,
+// so a block is just a big-endian u128.
+pub struct GHash {
+	h: u128,
+	y: u128,
+}
+
+impl GHash {
+	pub fn new(h: &[u8]) -> GHash {
+		GHash {h: block_to_u128(h), y: 0}
+	}
+
+	// Data is padded with zeros up to a full block
+	pub fn update(&mut self, data: &[u8]) {
+		for chunk in data.chunks(BLOCK_LEN) {
+			let mut block: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+			block[..chunk.len()].copy_from_slice(chunk);
+			self.y = gf_mul(self.y ^ block_to_u128(&block), self.h);
+		}
+	}
+
+	pub fn finalize(&self) -> [u8; BLOCK_LEN] {
+		self.y.to_be_bytes()
+	}
+}
+
+// Multiplication in GF(2^128) without branches on secret data
+fn gf_mul(x: u128, y: u128) -> u128 {
+	let mut z: u128 = 0;
+	let mut v: u128 = y;
+	for i in (0..128).rev() {
+		z ^= v & ((x >> i) & 1).wrapping_neg();
+		v = (v >> 1) ^ (R & (v & 1).wrapping_neg());
+	}
+	z
+}
+
+fn block_to_u128(block: &[u8]) -> u128 {
+	assert!(block.len() == BLOCK_LEN, "Block must have {0} bytes!", BLOCK_LEN);
+	block.iter().fold(0, |acc, &x| (acc << 8) | (x as u128))
+}
+
+fn inc32(block: &mut [u8]) {
+	let counter: u32 = block[12..].iter().fold(0, |acc, &x| (acc << 8) | (x as u32));
+	block[12..].copy_from_slice(&counter.wrapping_add(1).to_be_bytes());
+}
+
+pub struct GCM <BC = Speck_128_128> {
+	block_cipher: BC,
+	h: [u8; BLOCK_LEN],
+	tag_len: usize,
+}
+
+impl GCM {
+	pub fn new<U: Into<Block128>>(key: U, tag_len: usize) -> GCM {
+		GCM::with_cipher(Speck_128_128::new(key.into()), tag_len)
+	}
+}
+
+impl <BC: BlockCipher> GCM <BC> {
+	pub fn with_cipher(block_cipher: BC, tag_len: usize) -> GCM<BC> {
+		assert!(BC::BLOCK_SIZE == BLOCK_LEN, "GCM is defined for 128-bit blocks only!");
+		assert!(tag_len == 4 || tag_len == 8 || (12..=BLOCK_LEN).contains(&tag_len), "Tag must have 4, 8 or 12..16 bytes!");
+		let mut h: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		block_cipher.encrypt_block(&mut h);
+		GCM {block_cipher, h, tag_len}
+	}
+
+	pub fn gcm_encrypt_byte_array(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, GcmError> {
+		self.check_lengths(nonce, aad, plaintext.len())?;
+
+		let j0: [u8; BLOCK_LEN] = self.pre_counter_block(nonce);
+		let mut ciphertext: Vec<u8> = Vec::with_capacity(plaintext.len() + self.tag_len);
+		ciphertext.extend_from_slice(plaintext);
+		self.gctr(&j0, &mut ciphertext);
+
+		let tag: [u8; BLOCK_LEN] = self.tag(&j0, aad, &ciphertext);
+		ciphertext.extend_from_slice(&tag[..self.tag_len]);
+		Ok(ciphertext)
+	}
+
+	pub fn gcm_decrypt_byte_array(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, GcmError> {
+		if (ciphertext.len() < self.tag_len) { return Err(GcmError::WrongInput) };
+		let (encrypted, tag) = ciphertext.split_at(ciphertext.len() - self.tag_len);
+		self.check_lengths(nonce, aad, encrypted.len())?;
+
+		let j0: [u8; BLOCK_LEN] = self.pre_counter_block(nonce);
+		let expected_tag: [u8; BLOCK_LEN] = self.tag(&j0, aad, encrypted);
+		if (!util::constant_time_eq(&expected_tag[..self.tag_len], tag)) { return Err(GcmError::AuthenticationFailed) };
+
+		let mut decrypted: Vec<u8> = encrypted.to_vec();
+		self.gctr(&j0, &mut decrypted);
+		Ok(decrypted)
+	}
+
+	fn check_lengths(&self, nonce: &[u8], aad: &[u8], text_len: usize) -> Result<(), GcmError> {
+		if (nonce.is_empty()) { return Err(GcmError::EmptyNonce) };
+		if (text_len as u64 > MAX_PLAINTEXT_LEN || aad.len() as u64 >= (1 << 61) || nonce.len() as u64 >= (1 << 61)) { return Err(GcmError::InputTooLong) };
+		Ok(())
+	}
+
+	// 96-bit nonce is used directly, any other length goes through GHASH
+	fn pre_counter_block(&self, nonce: &[u8]) -> [u8; BLOCK_LEN] {
+		let mut j0: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		if (nonce.len() == 12) {
+			j0[..12].copy_from_slice(nonce);
+			j0[15] = 1;
+		} else {
+			let mut ghash: GHash = GHash::new(&self.h);
+			ghash.update(nonce);
+			let mut length_block: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+			length_block[8..].copy_from_slice(&(nonce.len() as u64 * 8).to_be_bytes());
+			ghash.update(&length_block);
+			j0 = ghash.finalize();
+		}
+		j0
+	}
+
+	// Keystream starts from the block after J0
+	fn gctr(&self, j0: &[u8; BLOCK_LEN], data: &mut [u8]) {
+		let mut counter_block: [u8; BLOCK_LEN] = *j0;
+		let mut keystream: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		for chunk in data.chunks_mut(BLOCK_LEN) {
+			inc32(&mut counter_block);
+			keystream.copy_from_slice(&counter_block);
+			self.block_cipher.encrypt_block(&mut keystream);
+			util::xor_in_place(chunk, &keystream);
+		}
+	}
+
+	fn tag(&self, j0: &[u8; BLOCK_LEN], aad: &[u8], ciphertext: &[u8]) -> [u8; BLOCK_LEN] {
+		let mut ghash: GHash = GHash::new(&self.h);
+		ghash.update(aad);
+		ghash.update(ciphertext);
+		let mut length_block: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		length_block[..8].copy_from_slice(&(aad.len() as u64 * 8).to_be_bytes());
+		length_block[8..].copy_from_slice(&(ciphertext.len() as u64 * 8).to_be_bytes());
+		ghash.update(&length_block);
+
+		let mut tag: [u8; BLOCK_LEN] = *j0;
+		self.block_cipher.encrypt_block(&mut tag);
+		util::xor_in_place(&mut tag, &ghash.finalize());
+		tag
+	}
+}
+
+#[test]
+fn ghash_works1() {
+	//GHASH(H, A, C) from test case 4 of the GCM specification (McGrew & Viega)
+	let h: [u8; 16] = [0xb8, 0x3b, 0x53, 0x37, 0x08, 0xbf, 0x53, 0x5d, 0x0a, 0xa6, 0xe5, 0x29, 0x80, 0xd5, 0x3b, 0x78];
+	let aad: [u8; 20] = [
+		0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad, 0xbe, 0xef, 0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad, 0xbe, 0xef,
+		0xab, 0xad, 0xda, 0xd2];
+	let ciphertext: [u8; 60] = [
+		0x42, 0x83, 0x1e, 0xc2, 0x21, 0x77, 0x74, 0x24, 0x4b, 0x72, 0x21, 0xb7, 0x84, 0xd0, 0xd4, 0x9c,
+		0xe3, 0xaa, 0x21, 0x2f, 0x2c, 0x02, 0xa4, 0xe0, 0x35, 0xc1, 0x7e, 0x23, 0x29, 0xac, 0xa1, 0x2e,
+		0x21, 0xd5, 0x14, 0xb2, 0x54, 0x66, 0x93, 0x1c, 0x7d, 0x8f, 0x6a, 0x5a, 0xac, 0x84, 0xaa, 0x05,
+		0x1b, 0xa3, 0x0b, 0x39, 0x6a, 0x0a, 0xac, 0x97, 0x3d, 0x58, 0xe0, 0x91];
+	let expected: [u8; 16] = [0x69, 0x8e, 0x57, 0xf7, 0x0e, 0x6e, 0xcc, 0x7f, 0xd9, 0x46, 0x3b, 0x72, 0x60, 0xa9, 0xae, 0x5f];
+
+	let mut ghash: GHash = GHash::new(&h);
+	ghash.update(&aad);
+	ghash.update(&ciphertext);
+	let mut length_block: [u8; 16] = [0; 16];
+	length_block[7] = 160;
+	length_block[14] = 1;
+	length_block[15] = 224;
+	ghash.update(&length_block);
+	assert_eq!(ghash.finalize(), expected);
+}
+
+#[test]
+fn gcm_works1() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let nonce: [u8; 12] = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88];
+	let aad: [u8; 20] = [
+		0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad, 0xbe, 0xef, 0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad, 0xbe, 0xef,
+		0xab, 0xad, 0xda, 0xd2];
+	let plaintext: Vec<u8> = (0x40..0x7c).collect();
+	let expected: [u8; 76] = [
+		0xa6, 0x99, 0xaf, 0xb7, 0x82, 0x8f, 0xe4, 0x96, 0xf5, 0xea, 0x49, 0x0e, 0x15, 0x24, 0x63, 0xb4,
+		0xb2, 0x1b, 0x05, 0x82, 0x2f, 0x84, 0xa9, 0xc3, 0x0a, 0x88, 0x65, 0x70, 0x7c, 0x61, 0xd1, 0x59,
+		0xac, 0x6d, 0xe6, 0xcc, 0x20, 0xf9, 0x49, 0x85, 0xdc, 0xf9, 0x31, 0x93, 0x42, 0x12, 0xd2, 0xdf,
+		0xf1, 0x58, 0x4e, 0x41, 0x29, 0xd6, 0xf5, 0x0d, 0x73, 0x84, 0xed, 0x66, 0xa7, 0x8b, 0xdb, 0x9b,
+		0x92, 0x13, 0x7b, 0xb4, 0x52, 0x29, 0xd3, 0x0c, 0xac, 0x83, 0x53, 0x51];
+
+	let c: GCM = GCM::new(&key, 16);
+	let ciphertext: Vec<u8> = c.gcm_encrypt_byte_array(&nonce, &aad, &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.gcm_decrypt_byte_array(&nonce, &aad, &ciphertext).unwrap(), plaintext);
+
+	//No plaintext: only the tag
+	let tag: Vec<u8> = c.gcm_encrypt_byte_array(&nonce, &aad, &[]).unwrap();
+	assert_eq!(tag.as_slice(), &[0x54, 0x15, 0x55, 0xe3, 0x98, 0x89, 0xf8, 0x4f, 0x16, 0xe4, 0xf5, 0x6c, 0xcc, 0x44, 0xfe, 0xdc][..]);
+	assert_eq!(c.gcm_decrypt_byte_array(&nonce, &aad, &tag).unwrap(), vec![]);
+}
+
+#[test]
+fn gcm_works2() {
+	//64-bit nonce and 96-bit tag
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let nonce: [u8; 8] = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad];
+	let plaintext: Vec<u8> = (0x60..0x70).collect();
+	let expected: [u8; 28] = [
+		0xb0, 0x0f, 0xaa, 0x10, 0x50, 0x60, 0xd3, 0x8e, 0x9b, 0xfa, 0x32, 0x3a, 0xee, 0xc6, 0x72, 0x5d,
+		0xc3, 0xa4, 0xbf, 0x70, 0x3e, 0x9e, 0xfc, 0x4b, 0x59, 0x36, 0x10, 0xb4];
+
+	let c: GCM = GCM::new(&key, 12);
+	let ciphertext: Vec<u8> = c.gcm_encrypt_byte_array(&nonce, &[], &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.gcm_decrypt_byte_array(&nonce, &[], &ciphertext).unwrap(), plaintext);
+}
+
+#[test]
+fn gcm_truncated_tag() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let nonce: [u8; 12] = [0; 12];
+	let plaintext: Vec<u8> = (0..50).collect();
+
+	let full: Vec<u8> = GCM::new(&key, 16).gcm_encrypt_byte_array(&nonce, b"header", &plaintext).unwrap();
+	for &tag_len in &[4, 8, 12, 13, 14, 15] {
+		let c: GCM = GCM::new(&key, tag_len);
+		let ciphertext: Vec<u8> = c.gcm_encrypt_byte_array(&nonce, b"header", &plaintext).unwrap();
+		assert_eq!(ciphertext.as_slice(), &full[.. 50 + tag_len]);
+		assert_eq!(c.gcm_decrypt_byte_array(&nonce, b"header", &ciphertext).unwrap(), plaintext);
+	}
+}
+
+#[test]
+#[should_panic]
+fn gcm_wrong_tag_len() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let _c: GCM = GCM::new(&key, 10);
+}
+
+#[test]
+fn gcm_authentication_fails() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let nonce: [u8; 12] = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88];
+	let plaintext: Vec<u8> = (0..40).collect();
+
+	let c: GCM = GCM::new(&key, 16);
+	let ciphertext: Vec<u8> = c.gcm_encrypt_byte_array(&nonce, b"header", &plaintext).unwrap();
+
+	util::assert_bit_flips_detected(&ciphertext, |x| c.gcm_decrypt_byte_array(&nonce, b"header", x), GcmError::AuthenticationFailed);
+	assert_eq!(c.gcm_decrypt_byte_array(&nonce, b"Header", &ciphertext), Err(GcmError::AuthenticationFailed));
+	assert_eq!(c.gcm_decrypt_byte_array(&nonce[..8], b"header", &ciphertext), Err(GcmError::AuthenticationFailed));
+	assert_eq!(c.gcm_decrypt_byte_array(&nonce, b"header", &ciphertext[..15]), Err(GcmError::WrongInput));
+	assert_eq!(c.gcm_encrypt_byte_array(&[], b"header", &plaintext), Err(GcmError::EmptyNonce));
+}
+
+#[test]
+fn gcm_tag_and_nonce_lengths() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let nonce: [u8; 12] = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88];
+	let plaintext: Vec<u8> = (0..40).collect();
+
+	let c16: GCM = GCM::new(&key, 16);
+	let c12: GCM = GCM::new(&key, 12);
+	let ciphertext: Vec<u8> = c16.gcm_encrypt_byte_array(&nonce, b"header", &plaintext).unwrap();
+
+	//A truncated tag is a prefix of the full one, but only the configured length is accepted
+	assert_eq!(c12.gcm_decrypt_byte_array(&nonce, b"header", &ciphertext[..52]).unwrap(), plaintext);
+	assert_eq!(c16.gcm_decrypt_byte_array(&nonce, b"header", &ciphertext[..52]), Err(GcmError::AuthenticationFailed));
+	assert_eq!(c12.gcm_decrypt_byte_array(&nonce, b"header", &ciphertext), Err(GcmError::AuthenticationFailed));
+
+	//Any nonce but a 96-bit one goes through GHASH, so zero padding gives another counter block
+	let mut long_nonce: Vec<u8> = nonce.to_vec();
+	long_nonce.extend_from_slice(&[0; 4]);
+	assert_eq!(c16.gcm_decrypt_byte_array(&long_nonce, b"header", &ciphertext), Err(GcmError::AuthenticationFailed));
+
+	//Without plaintext only the tag is left
+	let tag: Vec<u8> = c12.gcm_encrypt_byte_array(&nonce, b"header", &[]).unwrap();
+	assert_eq!(tag.len(), 12);
+	assert_eq!(c12.gcm_decrypt_byte_array(&nonce, b"header", &tag[..11]), Err(GcmError::WrongInput));
+}
@@ -0,0 +1,296 @@
+#![allow(unused_parens)]
+
+// GCM-SIV (RFC 8452 structure) for 128-bit block ciphers: nonce-misuse resistant with GHASH-like speed.
+// For each nonce the key-generating key derives an authentication key and an encryption key,
+// the latter as long as the key of the cipher, as with AES-128 and AES-256 in RFC 8452.
+// POLYVAL of associated data and plaintext gives the tag, and the tag is the initial counter of CTR.
+// A repeated nonce reveals only whether the same message was encrypted twice.
+
+use block128::Block128;
+use block_cipher::BlockCipher;
+use speck_128_128::Speck_128_128;
+use gcm::GHash;
+use util;
+#[cfg(test)]
+use speck::Speck_128_256;
+
+const BLOCK_LEN: usize = 16;
+pub const NONCE_LEN: usize = 12;
+const GHASH_R: u128 = 0xE1 << 120;
+const MAX_INPUT_LEN: u64 = 1 << 36;
+
+#[derive(Debug, PartialEq)]
+pub enum GcmSivError {
+	WrongNonce,
+	InputTooLong,
+	WrongInput,
+	AuthenticationFailed,
+}
+
+// POLYVAL works with little-endian blocks, it's computed through GHASH (RFC 8452, appendix A):
+// POLYVAL(H, X_1, ..., X_n) = ByteReverse(GHASH(mulX_GHASH(ByteReverse(H)), ByteReverse(X_1), ..., ByteReverse(X_n)))
+pub struct Polyval {
+	ghash: GHash,
+}
+
+impl Polyval {
+	pub fn new(h: &[u8]) -> Polyval {
+		let mut reversed: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		reversed.copy_from_slice(h);
+		reversed.reverse();
+		let value: u128 = u128::from_be_bytes(reversed);
+		let h_ghash: u128 = (value >> 1) ^ (GHASH_R & (value & 1).wrapping_neg());
+		Polyval {ghash: GHash::new(&h_ghash.to_be_bytes())}
+	}
+
+	// Data is padded with zeros up to a full block
+	pub fn update(&mut self, data: &[u8]) {
+		for chunk in data.chunks(BLOCK_LEN) {
+			let mut block: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+			block[..chunk.len()].copy_from_slice(chunk);
+			block.reverse();
+			self.ghash.update(&block);
+		}
+	}
+
+	pub fn finalize(&self) -> [u8; BLOCK_LEN] {
+		let mut result: [u8; BLOCK_LEN] = self.ghash.finalize();
+		result.reverse();
+		result
+	}
+}
+
+#[allow(non_camel_case_types)]
+pub struct GCM_SIV <BC = Speck_128_128> {
+	key_generating_cipher: BC,
+}
+
+impl GCM_SIV {
+	pub fn new<U: Into<Block128>>(key: U) -> GCM_SIV {
+		GCM_SIV::with_cipher(Speck_128_128::new(key.into()))
+	}
+}
+
+impl <BC: BlockCipher> GCM_SIV <BC> {
+	pub fn with_cipher(key_generating_cipher: BC) -> GCM_SIV<BC> {
+		assert!(BC::BLOCK_SIZE == BLOCK_LEN, "GCM-SIV is defined for 128-bit blocks only!");
+		assert!(BC::KEY_SIZE.is_multiple_of(8), "Key must have a multiple of 8 bytes!");
+		GCM_SIV {key_generating_cipher}
+	}
+
+	pub fn seal(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, GcmSivError> {
+		self.check_input(nonce, aad, plaintext.len())?;
+
+		let (auth_key, encryption_cipher) = self.derive_keys(nonce);
+		let tag: [u8; BLOCK_LEN] = Self::tag(&auth_key, &encryption_cipher, nonce, aad, plaintext);
+
+		let mut ciphertext: Vec<u8> = Vec::with_capacity(plaintext.len() + BLOCK_LEN);
+		ciphertext.extend_from_slice(plaintext);
+		Self::ctr(&encryption_cipher, &tag, &mut ciphertext);
+		ciphertext.extend_from_slice(&tag);
+		Ok(ciphertext)
+	}
+
+	pub fn open(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, GcmSivError> {
+		if (ciphertext.len() < BLOCK_LEN) { return Err(GcmSivError::WrongInput) };
+		let (encrypted, tag) = ciphertext.split_at(ciphertext.len() - BLOCK_LEN);
+		self.check_input(nonce, aad, encrypted.len())?;
+
+		let (auth_key, encryption_cipher) = self.derive_keys(nonce);
+		let mut decrypted: Vec<u8> = encrypted.to_vec();
+		Self::ctr(&encryption_cipher, tag, &mut decrypted);
+
+		let expected_tag: [u8; BLOCK_LEN] = Self::tag(&auth_key, &encryption_cipher, nonce, aad, &decrypted);
+		if (!util::constant_time_eq(&expected_tag, tag)) {
+			util::wipe(&mut decrypted);
+			return Err(GcmSivError::AuthenticationFailed);
+		}
+		Ok(decrypted)
+	}
+
+	fn check_input(&self, nonce: &[u8], aad: &[u8], text_len: usize) -> Result<(), GcmSivError> {
+		if (nonce.len() != NONCE_LEN) { return Err(GcmSivError::WrongNonce) };
+		if (text_len as u64 > MAX_INPUT_LEN || aad.len() as u64 > MAX_INPUT_LEN) { return Err(GcmSivError::InputTooLong) };
+		Ok(())
+	}
+
+	// Block i is the encryption of little-endian i and the nonce, only its first half is used
+	fn derive_keys(&self, nonce: &[u8]) -> ([u8; BLOCK_LEN], BC) {
+		let mut derived: Vec<u8> = vec![0; BLOCK_LEN + BC::KEY_SIZE];
+		for (i, half) in derived.chunks_mut(8).enumerate() {
+			let mut block: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+			block[..4].copy_from_slice(&(i as u32).to_le_bytes());
+			block[4..].copy_from_slice(nonce);
+			self.key_generating_cipher.encrypt_block(&mut block);
+			half.copy_from_slice(&block[..8]);
+		}
+
+		let mut auth_key: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		auth_key.copy_from_slice(&derived[..BLOCK_LEN]);
+		(auth_key, BC::from_key_bytes(&derived[BLOCK_LEN..]))
+	}
+
+	fn tag(auth_key: &[u8; BLOCK_LEN], encryption_cipher: &BC, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> [u8; BLOCK_LEN] {
+		let mut polyval: Polyval = Polyval::new(auth_key);
+		polyval.update(aad);
+		polyval.update(plaintext);
+		let mut length_block: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		length_block[..8].copy_from_slice(&(aad.len() as u64 * 8).to_le_bytes());
+		length_block[8..].copy_from_slice(&(plaintext.len() as u64 * 8).to_le_bytes());
+		polyval.update(&length_block);
+
+		let mut tag: [u8; BLOCK_LEN] = polyval.finalize();
+		util::xor_in_place(&mut tag, nonce);
+		tag[BLOCK_LEN - 1] &= 0x7F;
+		encryption_cipher.encrypt_block(&mut tag);
+		tag
+	}
+
+	// Counter block is the tag with the top bit set, its first 32 bits are a little-endian counter
+	fn ctr(encryption_cipher: &BC, tag: &[u8], data: &mut [u8]) {
+		let mut counter_block: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		counter_block.copy_from_slice(tag);
+		counter_block[BLOCK_LEN - 1] |= 0x80;
+		let mut counter: u32 = u32::from_le_bytes([counter_block[0], counter_block[1], counter_block[2], counter_block[3]]);
+
+		for chunk in data.chunks_mut(BLOCK_LEN) {
+			let mut keystream: [u8; BLOCK_LEN] = counter_block;
+			keystream[..4].copy_from_slice(&counter.to_le_bytes());
+			encryption_cipher.encrypt_block(&mut keystream);
+			util::xor_in_place(chunk, &keystream);
+			counter = counter.wrapping_add(1);
+		}
+	}
+}
+
+#[test]
+fn polyval_works1() {
+	//Example from RFC 8452, appendix A
+	let h: [u8; 16] = [0x25, 0x62, 0x93, 0x47, 0x58, 0x92, 0x42, 0x76, 0x1d, 0x31, 0xf8, 0x26, 0xba, 0x4b, 0x75, 0x7b];
+	let x: [u8; 32] = [
+		0x4f, 0x4f, 0x95, 0x66, 0x8c, 0x83, 0xdf, 0xb6, 0x40, 0x17, 0x62, 0xbb, 0x2d, 0x01, 0xa2, 0x62,
+		0xd1, 0xa2, 0x4d, 0xdd, 0x27, 0x21, 0xd0, 0x06, 0xbb, 0xe4, 0x5f, 0x20, 0xd3, 0xc9, 0xf3, 0x62];
+	let expected: [u8; 16] = [0xf7, 0xa3, 0xb4, 0x7b, 0x84, 0x61, 0x19, 0xfa, 0xe5, 0xb7, 0x86, 0x6c, 0xf5, 0xe5, 0xb7, 0x7e];
+
+	let mut polyval: Polyval = Polyval::new(&h);
+	polyval.update(&x);
+	assert_eq!(polyval.finalize(), expected);
+}
+
+#[test]
+fn gcm_siv_works1() {
+	//Key, nonce and messages of the RFC 8452 AEAD_AES_128_GCM_SIV samples
+	let key: [u64; 2] = [0x0000000000000001, 0x0000000000000000];
+	let nonce: [u8; 12] = [0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+	let plaintext2: [u8; 8] = [0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+	let mut plaintext3: [u8; 32] = [0; 32];
+	plaintext3[0] = 0x02;
+	plaintext3[16] = 0x03;
+	let expected1: [u8; 16] = [0x8c, 0x0e, 0x1c, 0x67, 0x12, 0x36, 0xbe, 0xaa, 0x80, 0x35, 0x91, 0x6e, 0x30, 0xcb, 0x38, 0x69];
+	let expected2: [u8; 24] = [
+		0x23, 0x14, 0xdc, 0xd6, 0x84, 0xea, 0xd1, 0x4a, 0xc5, 0xfb, 0xcc, 0x62, 0x6a, 0x4c, 0xa6, 0xb6,
+		0x6e, 0x03, 0x9e, 0x34, 0xc1, 0x90, 0x2a, 0x88];
+	let expected3: [u8; 48] = [
+		0xd6, 0xab, 0xac, 0x1a, 0xa0, 0x26, 0xdd, 0x62, 0x61, 0x37, 0x31, 0x59, 0xcd, 0x1e, 0x51, 0x4b,
+		0xff, 0x92, 0x37, 0xea, 0x22, 0xe6, 0xf8, 0x55, 0xed, 0x04, 0x83, 0x3d, 0xca, 0x9b, 0xa4, 0x4e,
+		0x89, 0xa9, 0xf1, 0x61, 0x70, 0x05, 0x1f, 0x1b, 0xa9, 0x21, 0x26, 0x7d, 0x1e, 0x03, 0x20, 0xe5];
+
+	let c: GCM_SIV = GCM_SIV::new(&key);
+	let ciphertext1: Vec<u8> = c.seal(&nonce, &[], &[]).unwrap();
+	assert_eq!(ciphertext1.as_slice(), &expected1[..]);
+	assert_eq!(c.open(&nonce, &[], &ciphertext1).unwrap(), vec![]);
+
+	let ciphertext2: Vec<u8> = c.seal(&nonce, &[], &plaintext2).unwrap();
+	assert_eq!(ciphertext2.as_slice(), &expected2[..]);
+	assert_eq!(c.open(&nonce, &[], &ciphertext2).unwrap().as_slice(), &plaintext2[..]);
+
+	let ciphertext3: Vec<u8> = c.seal(&nonce, &[0x01], &plaintext3).unwrap();
+	assert_eq!(ciphertext3.as_slice(), &expected3[..]);
+	assert_eq!(c.open(&nonce, &[0x01], &ciphertext3).unwrap().as_slice(), &plaintext3[..]);
+}
+
+#[test]
+fn gcm_siv_works2() {
+	let key: [u64; 2] = [0x0000000000000001, 0x0000000000000000];
+	let nonce: [u8; 12] = [0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+	let aad: Vec<u8> = (0x00..0x14).collect();
+	let plaintext: Vec<u8> = (0x30..0x58).collect();
+	let expected: [u8; 56] = [
+		0x81, 0xfc, 0x36, 0x84, 0xb3, 0xf1, 0x3d, 0x4c, 0x74, 0x68, 0xbb, 0x1d, 0xa5, 0xc1, 0x17, 0xed,
+		0xa3, 0x13, 0xd0, 0xdf, 0xde, 0x7e, 0x0a, 0xe3, 0x3e, 0x80, 0x07, 0x31, 0x10, 0x25, 0x7e, 0xa4,
+		0xa7, 0x1b, 0x12, 0xb5, 0x09, 0xc4, 0x04, 0x8b, 0x91, 0xc8, 0xae, 0x00, 0x6f, 0x3f, 0x6c, 0x88,
+		0x93, 0xf0, 0x5a, 0x4b, 0x6a, 0x59, 0x79, 0x34];
+
+	let c: GCM_SIV = GCM_SIV::new(&key);
+	let ciphertext: Vec<u8> = c.seal(&nonce, &aad, &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.open(&nonce, &aad, &ciphertext).unwrap(), plaintext);
+
+	//Nonce misuse: the same message gives the same ciphertext, a different one differs everywhere
+	assert_eq!(c.seal(&nonce, &aad, &plaintext).unwrap(), ciphertext);
+	let mut plaintext2: Vec<u8> = plaintext.clone();
+	plaintext2[39] ^= 0x01;
+	let ciphertext2: Vec<u8> = c.seal(&nonce, &aad, &plaintext2).unwrap();
+	assert!(ciphertext2[40..] != ciphertext[40..]);
+	for (block2, block) in ciphertext2[..40].chunks(16).zip(ciphertext[..40].chunks(16)) {
+		assert!(block2 != block);
+	}
+}
+
+#[test]
+fn gcm_siv_authentication_fails() {
+	let key: [u64; 2] = [0x0000000000000001, 0x0000000000000000];
+	let nonce: [u8; 12] = [0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+	let plaintext: Vec<u8> = (0..40).collect();
+
+	let c: GCM_SIV = GCM_SIV::new(&key);
+	let ciphertext: Vec<u8> = c.seal(&nonce, b"header", &plaintext).unwrap();
+	util::assert_bit_flips_detected(&ciphertext, |x| c.open(&nonce, b"header", x), GcmSivError::AuthenticationFailed);
+	let mut other_nonce: [u8; 12] = nonce;
+	other_nonce[11] = 0x01;
+	assert_eq!(c.open(&other_nonce, b"header", &ciphertext), Err(GcmSivError::AuthenticationFailed));
+	assert_eq!(c.open(&nonce, b"Header", &ciphertext), Err(GcmSivError::AuthenticationFailed));
+	assert_eq!(c.open(&nonce, b"header", &ciphertext[..15]), Err(GcmSivError::WrongInput));
+	assert_eq!(c.seal(&nonce[..8], b"header", &plaintext), Err(GcmSivError::WrongNonce));
+}
+
+#[test]
+fn gcm_siv_tag_is_counter() {
+	let key: [u64; 2] = [0x0000000000000001, 0x0000000000000000];
+	let nonce: [u8; 12] = [0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+	let plaintext: Vec<u8> = (0..40).collect();
+
+	let c: GCM_SIV = GCM_SIV::new(&key);
+	let ciphertext: Vec<u8> = c.seal(&nonce, b"header", &plaintext).unwrap();
+
+	//Keystream starts at the tag, so reordered or dropped blocks fail with the tag untouched
+	let mut swapped: Vec<u8> = ciphertext[16..32].to_vec();
+	swapped.extend_from_slice(&ciphertext[..16]);
+	swapped.extend_from_slice(&ciphertext[32..]);
+	assert_eq!(c.open(&nonce, b"header", &swapped), Err(GcmSivError::AuthenticationFailed));
+	assert_eq!(c.open(&nonce, b"header", &ciphertext[16..]), Err(GcmSivError::AuthenticationFailed));
+
+	//Only 96-bit nonces, on both sides
+	assert_eq!(c.open(&[0; 16], b"header", &ciphertext), Err(GcmSivError::WrongNonce));
+	assert_eq!(c.seal(&[], b"header", &plaintext), Err(GcmSivError::WrongNonce));
+}
+
+#[test]
+fn gcm_siv_speck_128_256_works() {
+	//A 256-bit key derives a 256-bit encryption key from six blocks, as AEAD_AES_256_GCM_SIV does.
+	//Regression values of this implementation.
+	let key: Vec<u8> = (0..32).collect();
+	let nonce: [u8; 12] = [0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+	let aad: Vec<u8> = (0x00..0x14).collect();
+	let plaintext: Vec<u8> = (0x30..0x58).collect();
+	let expected: [u8; 56] = [
+		0x08, 0x53, 0x20, 0x4c, 0x1d, 0xa2, 0xe1, 0xde, 0x7f, 0xd7, 0x36, 0xe6, 0x0f, 0x66, 0x51, 0x49,
+		0xb6, 0xaa, 0x67, 0xa1, 0xa8, 0x60, 0xcd, 0xa6, 0xbf, 0xd5, 0xd7, 0xe2, 0xbb, 0x38, 0x48, 0x16,
+		0x80, 0x68, 0x11, 0x2e, 0xeb, 0x9b, 0xc8, 0x13, 0x8e, 0x11, 0x8c, 0x29, 0x0e, 0x9b, 0xc7, 0xbe,
+		0xa8, 0xda, 0xb8, 0x2e, 0x10, 0xe6, 0x66, 0x7b];
+
+	let c: GCM_SIV<Speck_128_256> = GCM_SIV::with_cipher(Speck_128_256::from_key_bytes(&key));
+	let ciphertext: Vec<u8> = c.seal(&nonce, &aad, &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.open(&nonce, &aad, &ciphertext).unwrap(), plaintext);
+}
@@ -0,0 +1,153 @@
+#![allow(unused_parens)]
+
+// Infinite Garble Extension (Telegram MTProto style): C[i] = E(P[i] ^ C[i-1]) ^ P[i-1].
+// IV has two blocks: the first one is C[0], the second one is P[0], as in OpenSSL.
+// A damaged ciphertext block garbles all of the following plaintext.
+
+use block128::Block128;
+use block_cipher::{BlockCipher, CipherErrors};
+use speck_128_128::Speck_128_128;
+use padding::PaddingGenerator;
+use util;
+use std::marker::PhantomData;
+#[cfg(test)]
+use pkcs7::PKCS7;
+#[cfg(test)]
+use speck::Speck_64_128;
+
+pub struct IGE <PG, BC = Speck_128_128> {
+	iv: Vec<u8>,
+	block_cipher: BC,
+	padd_generator: PhantomData<PG>,
+}
+
+impl <PG: PaddingGenerator> IGE <PG> {
+	pub fn new<U: Into<Block128>>(iv1: U, iv2: U, key: U) -> IGE<PG> {
+		let iv1_block: Block128 = iv1.into();
+		let iv2_block: Block128 = iv2.into();
+		let mut iv_bytes: [u8; 32] = [0; 32];
+		util::words_to_bytes_le(&[iv1_block.get_a(), iv1_block.get_b(), iv2_block.get_a(), iv2_block.get_b()], &mut iv_bytes);
+		IGE::with_cipher(&iv_bytes, Speck_128_128::new(key.into()))
+	}
+}
+
+impl <PG: PaddingGenerator, BC: BlockCipher> IGE <PG, BC> {
+	pub fn with_cipher(iv: &[u8], block_cipher: BC) -> IGE<PG, BC> {
+		assert!(iv.len() == 2 * BC::BLOCK_SIZE, "IV must have {0} bytes!", 2 * BC::BLOCK_SIZE);
+		IGE {iv: iv.to_vec(), block_cipher, padd_generator: PhantomData::<PG> }
+	}
+
+	pub fn ige_encrypt_byte_array(&self, plaintext: &[u8]) -> Result<Vec<u8>, CipherErrors> {
+		if (plaintext.is_empty()) { return Err(CipherErrors::WrongInput) };
+
+		let mut last_block: Vec<u8> = vec![0; BC::BLOCK_SIZE];
+		PG::set_padding(plaintext, &mut last_block, BC::BLOCK_SIZE);
+
+		let full_blocks_len: usize = plaintext.len() - plaintext.len() % BC::BLOCK_SIZE;
+		let mut ciphertext: Vec<u8> = Vec::with_capacity(full_blocks_len + last_block.len());
+		ciphertext.extend_from_slice(&plaintext[..full_blocks_len]);
+		ciphertext.extend_from_slice(&last_block);
+
+		let (mut previous_c, mut previous_p): (Vec<u8>, Vec<u8>) = (self.iv[..BC::BLOCK_SIZE].to_vec(), self.iv[BC::BLOCK_SIZE..].to_vec());
+		let mut plain_block: Vec<u8> = vec![0; BC::BLOCK_SIZE];
+		for block in ciphertext.chunks_mut(BC::BLOCK_SIZE) {
+			plain_block.copy_from_slice(block);
+			util::xor_in_place(block, &previous_c);
+			self.block_cipher.encrypt_block(block);
+			util::xor_in_place(block, &previous_p);
+			previous_c.copy_from_slice(block);
+			previous_p.copy_from_slice(&plain_block);
+		}
+
+		Ok(ciphertext)
+	}
+
+	pub fn ige_decrypt_byte_array(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherErrors> {
+		if (ciphertext.is_empty() || !ciphertext.len().is_multiple_of(BC::BLOCK_SIZE)) { return Err(CipherErrors::WrongInput) };
+
+		let mut decrypted: Vec<u8> = ciphertext.to_vec();
+
+		let mut previous_p: Vec<u8> = self.iv[BC::BLOCK_SIZE..].to_vec();
+		let mut previous_c: &[u8] = &self.iv[..BC::BLOCK_SIZE];
+		for (block, encrypted) in decrypted.chunks_mut(BC::BLOCK_SIZE).zip(ciphertext.chunks(BC::BLOCK_SIZE)) {
+			util::xor_in_place(block, &previous_p);
+			self.block_cipher.decrypt_block(block);
+			util::xor_in_place(block, previous_c);
+			previous_p.copy_from_slice(block);
+			previous_c = encrypted;
+		}
+
+		match PG::remove_padding(&decrypted, BC::BLOCK_SIZE) {
+			Err(_)        => Err(CipherErrors::WrongPadding),
+			Ok(plaintext_len) => {
+				decrypted.truncate(plaintext_len);
+				Ok(decrypted)
+			},
+		}
+	}
+}
+
+#[test]
+fn ige_works1() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let iv: [u8; 32]  = [
+		0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xab, 0xac, 0xad, 0xae, 0xaf,
+		0xb0, 0xb1, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6, 0xb7, 0xb8, 0xb9, 0xba, 0xbb, 0xbc, 0xbd, 0xbe, 0xbf];
+	let plaintext: [u8; 35] = [
+		0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x3b, 0x3c, 0x3d, 0x3e, 0x3f,
+		0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f,
+		0x50, 0x51, 0x52];
+	let expected: [u8; 48] = [
+		0x40, 0x22, 0x6f, 0x09, 0x75, 0x72, 0x1c, 0x15, 0x3b, 0x7a, 0x27, 0x45, 0x2b, 0x03, 0x2c, 0x3b,
+		0x4f, 0x05, 0xa7, 0x3d, 0xdb, 0x79, 0x45, 0x5a, 0xc1, 0xeb, 0x1a, 0x18, 0x34, 0xf6, 0x1f, 0x74,
+		0xe7, 0xb4, 0x3d, 0x2b, 0x0d, 0xa7, 0x72, 0xc7, 0xa1, 0x61, 0xd3, 0x0c, 0xdf, 0xb8, 0xec, 0xb3];
+
+	let c: IGE<PKCS7> = IGE::with_cipher(&iv, Speck_128_128::new(&key));
+
+	let ciphertext: Vec<u8> = c.ige_encrypt_byte_array(&plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.ige_decrypt_byte_array(&ciphertext).unwrap().as_slice(), &plaintext[..]);
+}
+
+#[test]
+fn ige_works2() {
+	let key: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x08, 0x09, 0x0a, 0x0b, 0x10, 0x11, 0x12, 0x13, 0x18, 0x19, 0x1a, 0x1b];
+	let iv: [u8; 16]  = [0xD2, 0xC4, 0xB7, 0xD9, 0x6C, 0x49, 0x16, 0x0E, 0x4E, 0xFE, 0x0C, 0x3E, 0x3B, 0x9F, 0xFD, 0x85];
+	let plaintext: Vec<u8> = (0..45).collect();
+
+	let c: IGE<PKCS7, Speck_64_128> = IGE::with_cipher(&iv, Speck_64_128::from_key_bytes(&key));
+
+	for len in 1 .. plaintext.len() {
+		let ciphertext: Vec<u8> = c.ige_encrypt_byte_array(&plaintext[..len]).unwrap();
+		assert_eq!(ciphertext.len(), (len / 8 + 1) * 8);
+		assert_eq!(c.ige_decrypt_byte_array(&ciphertext).unwrap(), &plaintext[..len]);
+	}
+
+	assert!(c.ige_encrypt_byte_array(&[]).is_err());
+	assert!(c.ige_decrypt_byte_array(&plaintext[..12]).is_err());
+}
+
+#[test]
+fn ige_error_propagation() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let iv1: [u64; 2] = [0xD2C4B7D96C49160E, 0x4EFE0C3E3B9FFD85];
+	let iv2: [u64; 2] = [0xAFF92B19D2240A90, 0xDD55C781B2E48BB0];
+	let plaintext: Vec<u8> = (0..64).collect();
+
+	let c: IGE<PKCS7> = IGE::new(&iv1, &iv2, &key);
+	let ciphertext: Vec<u8> = c.ige_encrypt_byte_array(&plaintext).unwrap();
+
+	//Damage of any block, even the first one, reaches the padding at the end
+	for &position in &[0, 20, 47] {
+		let mut damaged: Vec<u8> = ciphertext.clone();
+		damaged[position] ^= 0x80;
+		match c.ige_decrypt_byte_array(&damaged) {
+			Err(CipherErrors::WrongPadding) => {},
+			_                               => panic!("Damage should propagate up to the padding!"),
+		}
+	}
+
+	//Wrong IV garbles everything as well
+	let wrong_iv: IGE<PKCS7> = IGE::new(&iv2, &iv1, &key);
+	assert!(wrong_iv.ige_decrypt_byte_array(&ciphertext).is_err());
+}
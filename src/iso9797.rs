@@ -0,0 +1,123 @@
+#![allow(unused_parens)]
+
+// Padding methods of ISO/IEC 9797-1 for MAC algorithms.
+// Method 1: zeros up to a full block (an empty message becomes one zero block).
+// Method 2: 0x80 and zeros, always added. It can be removed, so it also works for encryption (as in ISO/IEC 7816-4).
+// Method 3: method 1 with a block holding the message length in bits prepended.
+
+use padding::*;
+
+pub struct ISO9797M1;
+pub struct ISO9797M2;
+pub struct ISO9797M3;
+
+// Appends zeros up to a full block
+fn pad_with_zeros(padded: &mut Vec<u8>, block_len: usize) {
+	let padded_len: usize = padded.len().div_ceil(block_len) * block_len;
+	padded.resize(padded_len, 0);
+}
+
+impl MacPaddingGenerator for ISO9797M1 {
+	fn pad_message (message: &[u8], block_len: usize) -> Vec<u8> {
+		assert!(block_len != 0, "Sorry, wrong block length!");
+		let mut padded: Vec<u8> = message.to_vec();
+		pad_with_zeros(&mut padded, block_len);
+		if (padded.is_empty()) {
+			padded.resize(block_len, 0);
+		}
+		padded
+	}
+}
+
+impl MacPaddingGenerator for ISO9797M2 {
+	fn pad_message (message: &[u8], block_len: usize) -> Vec<u8> {
+		assert!(block_len != 0, "Sorry, wrong block length!");
+		let mut padded: Vec<u8> = Vec::with_capacity(message.len() + block_len);
+		padded.extend_from_slice(message);
+		padded.push(0x80);
+		pad_with_zeros(&mut padded, block_len);
+		padded
+	}
+}
+
+impl PaddingGenerator for ISO9797M2 {
+	fn set_padding (plaintext: &[u8], padding: &mut[u8], block_len: usize) {
+		assert!(block_len != 0, "Sorry, wrong block length!");
+		assert!(padding.len() == block_len, "Padding lenght should be equal to block length!");
+
+		let length: usize = plaintext.len();
+		let appendix: usize = length % block_len;
+
+		padding[0..appendix].clone_from_slice(&plaintext[length-appendix..length]);
+		padding[appendix] = 0x80;
+		for x in &mut padding[appendix + 1 ..] {
+			*x = 0;
+		}
+	}
+
+	fn remove_padding (ciphertext: &[u8], block_len: usize) -> Result<usize, PaddingError> {
+		if (ciphertext.is_empty() || !ciphertext.len().is_multiple_of(block_len)) {
+			return Err(PaddingError::WrongCiphertextLength);
+		}
+
+		let last_block: &[u8] = &ciphertext[ciphertext.len() - block_len ..];
+		match last_block.iter().rposition(|&x| x != 0) {
+			Some(position) if last_block[position] == 0x80 => Ok(ciphertext.len() - block_len + position),
+			_                                              => Err(PaddingError::WrongPadding),
+		}
+	}
+}
+
+// The length block is big-endian and right-justified
+impl MacPaddingGenerator for ISO9797M3 {
+	fn pad_message (message: &[u8], block_len: usize) -> Vec<u8> {
+		assert!(block_len >= 8, "Sorry, wrong block length!");
+		let mut padded: Vec<u8> = vec![0; block_len];
+		let bit_len: u64 = message.len() as u64 * 8;
+		padded[block_len - 8 ..].copy_from_slice(&bit_len.to_be_bytes());
+		padded.extend_from_slice(message);
+		pad_with_zeros(&mut padded, block_len);
+		padded
+	}
+}
+
+#[test]
+fn iso9797_m1_block_8() {
+	assert_eq!(ISO9797M1::pad_message(&[], 8), vec![0; 8]);
+	assert_eq!(ISO9797M1::pad_message(&[0xAA, 0xBB, 0xCC], 8), vec![0xAA, 0xBB, 0xCC, 0, 0, 0, 0, 0]);
+	assert_eq!(ISO9797M1::pad_message(&[0x11; 8], 8), vec![0x11; 8]);
+	assert_eq!(ISO9797M1::pad_message(&[0x11; 9], 8).len(), 16);
+}
+
+#[test]
+fn iso9797_m2_block_8() {
+	assert_eq!(ISO9797M2::pad_message(&[], 8), vec![0x80, 0, 0, 0, 0, 0, 0, 0]);
+	assert_eq!(ISO9797M2::pad_message(&[0xAA, 0xBB, 0xCC], 8), vec![0xAA, 0xBB, 0xCC, 0x80, 0, 0, 0, 0]);
+	assert_eq!(ISO9797M2::pad_message(&[0x11; 7], 8), vec![0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x80]);
+	assert_eq!(ISO9797M2::pad_message(&[0x11; 8], 8).len(), 16);
+}
+
+#[test]
+fn iso9797_m2_remove() {
+	let text1: [u8; 3] = [0xAA, 0xBB, 0x80];
+	let mut padding1: [u8; 8] = [0; 8];
+	ISO9797M2::set_padding(&text1, &mut padding1, 8);
+	assert_eq!(padding1, [0xAA, 0xBB, 0x80, 0x80, 0, 0, 0, 0]);
+	assert_eq!(ISO9797M2::remove_padding(&padding1, 8).unwrap(), 3);
+
+	let text2: [u8; 16] = [0x80; 16];
+	let padded2: Vec<u8> = ISO9797M2::pad_message(&text2, 8);
+	assert_eq!(ISO9797M2::remove_padding(&padded2, 8).unwrap(), 16);
+
+	assert!(ISO9797M2::remove_padding(&[0x11, 0, 0, 0, 0, 0, 0, 0], 8).is_err());
+	assert!(ISO9797M2::remove_padding(&[0; 8], 8).is_err());
+	assert!(ISO9797M2::remove_padding(&[0x80; 7], 8).is_err());
+}
+
+#[test]
+fn iso9797_m3_block_8() {
+	assert_eq!(ISO9797M3::pad_message(&[], 8), vec![0; 8]);
+	assert_eq!(ISO9797M3::pad_message(&[0xAA, 0xBB, 0xCC], 8), vec![0, 0, 0, 0, 0, 0, 0, 24, 0xAA, 0xBB, 0xCC, 0, 0, 0, 0, 0]);
+	assert_eq!(ISO9797M3::pad_message(&[0x11; 40], 16)[..16], [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01, 0x40]);
+	assert_eq!(ISO9797M3::pad_message(&[0x11; 40], 16).len(), 64);
+}
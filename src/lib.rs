@@ -2,8 +2,31 @@
 
 pub mod util;
 pub mod block128;
+pub mod block_cipher;
 pub mod padding;
 pub mod pkcs7;
+pub mod iso9797;
 pub mod speck_128_128;
+pub mod speck;
+pub mod simon;
 pub mod zuc_128;
+pub mod zuc_128_x4;
+pub mod eia3;
+pub mod eea3;
+pub mod zuc_256;
 pub mod cbc;
+pub mod pcbc;
+pub mod ige;
+pub mod ctr;
+pub mod ofb;
+pub mod cfb;
+pub mod xts;
+pub mod gcm;
+pub mod gcm_siv;
+pub mod ccm;
+pub mod eax;
+pub mod ocb;
+pub mod siv;
+pub mod mac;
+#[cfg(feature = "hazmat")]
+pub mod ecb;
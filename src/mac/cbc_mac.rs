@@ -0,0 +1,178 @@
+#![allow(unused_parens)]
+
+// CBC-MAC based algorithms of ISO/IEC 9797-1, for any block size.
+// Algorithm 1: plain CBC-MAC of the padded message.
+// Algorithm 2: CBC-MAC, then the last block is encrypted with the second key.
+// Algorithm 3 (retail MAC, ANSI X9.19): CBC-MAC, then the last block is decrypted with the second key
+// and encrypted with the first key again.
+// Padding method is a type parameter, as in CBC mode.
+
+use block_cipher::BlockCipher;
+use padding::MacPaddingGenerator;
+use speck_128_128::Speck_128_128;
+use util;
+use std::marker::PhantomData;
+#[cfg(test)]
+use iso9797::{ISO9797M1, ISO9797M2, ISO9797M3};
+#[cfg(test)]
+use speck::Speck_64_128;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MacAlgorithm {Algorithm1, Algorithm2, Algorithm3}
+
+pub struct CbcMac <PM, BC = Speck_128_128> {
+	algorithm: MacAlgorithm,
+	block_cipher: BC,
+	second_cipher: Option<BC>,
+	tag_len: usize,
+	padd_generator: PhantomData<PM>,
+}
+
+impl <PM: MacPaddingGenerator, BC: BlockCipher> CbcMac <PM, BC> {
+	pub fn algorithm1(block_cipher: BC, tag_len: usize) -> CbcMac<PM, BC> {
+		CbcMac::with_ciphers(MacAlgorithm::Algorithm1, block_cipher, None, tag_len)
+	}
+
+	pub fn algorithm2(block_cipher: BC, final_cipher: BC, tag_len: usize) -> CbcMac<PM, BC> {
+		CbcMac::with_ciphers(MacAlgorithm::Algorithm2, block_cipher, Some(final_cipher), tag_len)
+	}
+
+	pub fn algorithm3(block_cipher: BC, second_cipher: BC, tag_len: usize) -> CbcMac<PM, BC> {
+		CbcMac::with_ciphers(MacAlgorithm::Algorithm3, block_cipher, Some(second_cipher), tag_len)
+	}
+
+	fn with_ciphers(algorithm: MacAlgorithm, block_cipher: BC, second_cipher: Option<BC>, tag_len: usize) -> CbcMac<PM, BC> {
+		assert!(tag_len > 0 && tag_len <= BC::BLOCK_SIZE, "Tag must have 1..{0} bytes!", BC::BLOCK_SIZE);
+		CbcMac {algorithm, block_cipher, second_cipher, tag_len, padd_generator: PhantomData::<PM>}
+	}
+
+	pub fn algorithm(&self) -> MacAlgorithm {
+		self.algorithm
+	}
+
+	pub fn cbc_mac_byte_array(&self, message: &[u8]) -> Vec<u8> {
+		let padded: Vec<u8> = PM::pad_message(message, BC::BLOCK_SIZE);
+
+		let mut mac: Vec<u8> = vec![0; BC::BLOCK_SIZE];
+		for block in padded.chunks(BC::BLOCK_SIZE) {
+			util::xor_in_place(&mut mac, block);
+			self.block_cipher.encrypt_block(&mut mac);
+		}
+
+		match (self.algorithm, self.second_cipher.as_ref()) {
+			(MacAlgorithm::Algorithm2, Some(final_cipher)) => final_cipher.encrypt_block(&mut mac),
+			(MacAlgorithm::Algorithm3, Some(second_cipher)) => {
+				second_cipher.decrypt_block(&mut mac);
+				self.block_cipher.encrypt_block(&mut mac);
+			},
+			_ => {},
+		}
+
+		mac.truncate(self.tag_len);
+		mac
+	}
+
+	pub fn verify(&self, message: &[u8], tag: &[u8]) -> bool {
+		util::constant_time_eq(&self.cbc_mac_byte_array(message), tag)
+	}
+}
+
+#[cfg(test)]
+const TEST_KEY1: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+#[cfg(test)]
+const TEST_KEY2: [u64; 2] = [0x1716151413121110, 0x1f1e1d1c1b1a1918];
+
+#[cfg(test)]
+fn check_kats<PM: MacPaddingGenerator>(algorithm: MacAlgorithm, expected: &[[u8; 16]; 3]) {
+	let message: Vec<u8> = (0x30..0x58).collect();
+	let c: CbcMac<PM> = match algorithm {
+		MacAlgorithm::Algorithm1 => CbcMac::algorithm1(Speck_128_128::new(&TEST_KEY1), 16),
+		MacAlgorithm::Algorithm2 => CbcMac::algorithm2(Speck_128_128::new(&TEST_KEY1), Speck_128_128::new(&TEST_KEY2), 16),
+		MacAlgorithm::Algorithm3 => CbcMac::algorithm3(Speck_128_128::new(&TEST_KEY1), Speck_128_128::new(&TEST_KEY2), 16),
+	};
+	for (i, &len) in [0, 16, 40].iter().enumerate() {
+		let tag: Vec<u8> = c.cbc_mac_byte_array(&message[..len]);
+		assert_eq!(tag.as_slice(), &expected[i][..]);
+		assert!(c.verify(&message[..len], &tag));
+	}
+}
+
+#[test]
+fn cbc_mac_algorithm1() {
+	check_kats::<ISO9797M1>(MacAlgorithm::Algorithm1, &[
+		[0x3d, 0xf1, 0xae, 0xb7, 0x75, 0x2d, 0xbb, 0x06, 0xc5, 0xde, 0xea, 0x14, 0x3d, 0xa6, 0xd6, 0x30],
+		[0xb5, 0x3f, 0xa8, 0x5c, 0xde, 0xc7, 0x04, 0x6d, 0x85, 0x0a, 0xfc, 0xde, 0xec, 0x82, 0x8a, 0xc4],
+		[0x72, 0x7b, 0x1a, 0xc8, 0xb3, 0xc2, 0x83, 0x7d, 0xfc, 0x82, 0x87, 0xcc, 0x6d, 0xaa, 0x34, 0x6d]]);
+	check_kats::<ISO9797M2>(MacAlgorithm::Algorithm1, &[
+		[0x04, 0x16, 0x2e, 0xbf, 0x45, 0x4a, 0x90, 0xa8, 0xef, 0xeb, 0x31, 0x5c, 0x24, 0x72, 0x45, 0x8e],
+		[0x83, 0x13, 0x04, 0x0e, 0xcc, 0x07, 0xb8, 0xf6, 0xb8, 0x22, 0x57, 0xe0, 0x99, 0xb7, 0x58, 0xeb],
+		[0xc0, 0x6a, 0xd8, 0x77, 0xf0, 0x67, 0x4f, 0x65, 0xe5, 0x7f, 0xa3, 0xc5, 0x3c, 0x45, 0xb1, 0xa0]]);
+	check_kats::<ISO9797M3>(MacAlgorithm::Algorithm1, &[
+		[0x3d, 0xf1, 0xae, 0xb7, 0x75, 0x2d, 0xbb, 0x06, 0xc5, 0xde, 0xea, 0x14, 0x3d, 0xa6, 0xd6, 0x30],
+		[0x1d, 0x4f, 0xed, 0xec, 0x94, 0x8b, 0x46, 0x49, 0xc6, 0x97, 0x9d, 0xf2, 0x3d, 0xea, 0xf8, 0x31],
+		[0x36, 0x60, 0xef, 0x50, 0x82, 0x21, 0x10, 0x48, 0xa6, 0x71, 0xf7, 0x59, 0x37, 0x15, 0x2d, 0x4a]]);
+}
+
+#[test]
+fn cbc_mac_algorithm2() {
+	check_kats::<ISO9797M1>(MacAlgorithm::Algorithm2, &[
+		[0xd9, 0xe5, 0x52, 0x2c, 0xc3, 0x9d, 0xae, 0xc8, 0x82, 0x01, 0x22, 0xf9, 0x68, 0xb1, 0x3f, 0x78],
+		[0x95, 0x6b, 0xd7, 0xc3, 0xc5, 0x44, 0x3a, 0x5d, 0xe1, 0x96, 0x50, 0x07, 0x91, 0x96, 0x6a, 0x21],
+		[0xe3, 0x36, 0xf3, 0x5d, 0x11, 0x1b, 0x8f, 0xb5, 0x34, 0x5b, 0xbf, 0xf9, 0x2d, 0x41, 0x89, 0x98]]);
+	check_kats::<ISO9797M2>(MacAlgorithm::Algorithm2, &[
+		[0x36, 0x62, 0xbd, 0x89, 0x36, 0x5f, 0x3d, 0xd8, 0x4b, 0x02, 0xc7, 0x7d, 0x94, 0x35, 0x3e, 0x5e],
+		[0xdc, 0x64, 0xea, 0x5a, 0xf6, 0xf1, 0xa9, 0x21, 0x82, 0x5c, 0x0f, 0x36, 0xb7, 0x6a, 0x67, 0x5d],
+		[0x60, 0x19, 0x53, 0x1c, 0x9d, 0x05, 0x8a, 0xe7, 0x9b, 0xed, 0xf3, 0xe0, 0x59, 0x50, 0x85, 0xd4]]);
+	check_kats::<ISO9797M3>(MacAlgorithm::Algorithm2, &[
+		[0xd9, 0xe5, 0x52, 0x2c, 0xc3, 0x9d, 0xae, 0xc8, 0x82, 0x01, 0x22, 0xf9, 0x68, 0xb1, 0x3f, 0x78],
+		[0xf6, 0x61, 0xa6, 0x0a, 0xe8, 0xc6, 0x44, 0xec, 0xbf, 0xfe, 0xed, 0x77, 0x68, 0x2b, 0x3d, 0xa4],
+		[0x26, 0x9e, 0x5c, 0x16, 0x8b, 0x94, 0x40, 0x71, 0x46, 0x02, 0x11, 0x45, 0x9c, 0xc3, 0x3e, 0x5d]]);
+}
+
+#[test]
+fn cbc_mac_algorithm3() {
+	check_kats::<ISO9797M1>(MacAlgorithm::Algorithm3, &[
+		[0xbe, 0xfe, 0xdf, 0x7f, 0xe4, 0xc0, 0x06, 0xd4, 0xfd, 0x76, 0xf6, 0xdb, 0x2a, 0x1d, 0x46, 0x86],
+		[0x2e, 0x77, 0xe6, 0xe3, 0x83, 0x11, 0xbd, 0x81, 0x51, 0xde, 0xc8, 0x61, 0xcf, 0x74, 0x58, 0xb8],
+		[0xb7, 0x39, 0xcd, 0xf8, 0xd6, 0xa5, 0x1a, 0xe5, 0x87, 0xcc, 0xf9, 0xa7, 0x71, 0xf5, 0xf4, 0x08]]);
+	check_kats::<ISO9797M2>(MacAlgorithm::Algorithm3, &[
+		[0x5a, 0x42, 0x78, 0xac, 0x66, 0x43, 0x89, 0x72, 0x5c, 0xee, 0x0d, 0x69, 0x4f, 0xac, 0x57, 0xaa],
+		[0x97, 0x18, 0x6b, 0x72, 0x78, 0x8d, 0x46, 0xdc, 0x3a, 0x47, 0xff, 0xd3, 0x4f, 0x86, 0x00, 0x15],
+		[0x1c, 0xb7, 0x77, 0x9e, 0x7a, 0x8f, 0x4c, 0x65, 0x4f, 0x74, 0x31, 0xc6, 0xcc, 0x87, 0x17, 0xbc]]);
+	check_kats::<ISO9797M3>(MacAlgorithm::Algorithm3, &[
+		[0xbe, 0xfe, 0xdf, 0x7f, 0xe4, 0xc0, 0x06, 0xd4, 0xfd, 0x76, 0xf6, 0xdb, 0x2a, 0x1d, 0x46, 0x86],
+		[0x96, 0x1a, 0x82, 0x6a, 0xf6, 0x41, 0xe6, 0xb7, 0x72, 0xde, 0x1d, 0x30, 0xd5, 0xae, 0x41, 0xed],
+		[0x99, 0x96, 0xec, 0xd4, 0xff, 0x7d, 0x2d, 0x08, 0xd5, 0x15, 0x9a, 0x8a, 0x09, 0x6c, 0x25, 0xa1]]);
+}
+
+#[test]
+fn cbc_mac_retail_64() {
+	//Retail MAC with a 64-bit block and 4-byte tag, as payment terminals use it
+	let key1: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x08, 0x09, 0x0a, 0x0b, 0x10, 0x11, 0x12, 0x13, 0x18, 0x19, 0x1a, 0x1b];
+	let key2: [u8; 16] = [0x1b, 0x1a, 0x19, 0x18, 0x13, 0x12, 0x11, 0x10, 0x0b, 0x0a, 0x09, 0x08, 0x03, 0x02, 0x01, 0x00];
+	let message: &[u8] = b"Now is the time for all ";
+
+	let alg1: CbcMac<ISO9797M1, Speck_64_128> = CbcMac::algorithm1(Speck_64_128::from_key_bytes(&key1), 8);
+	let alg3: CbcMac<ISO9797M1, Speck_64_128> = CbcMac::algorithm3(Speck_64_128::from_key_bytes(&key1), Speck_64_128::from_key_bytes(&key2), 4);
+	let tag: Vec<u8> = alg3.cbc_mac_byte_array(message);
+	assert_eq!(tag.len(), 4);
+	assert!(alg3.verify(message, &tag));
+	assert!(!alg3.verify(&message[1..], &tag));
+	assert!(tag != alg1.cbc_mac_byte_array(message)[..4]);
+
+	//With equal keys algorithm 3 is the same as algorithm 1
+	let same_keys: CbcMac<ISO9797M1, Speck_64_128> = CbcMac::algorithm3(Speck_64_128::from_key_bytes(&key1), Speck_64_128::from_key_bytes(&key1), 8);
+	assert_eq!(same_keys.cbc_mac_byte_array(message), alg1.cbc_mac_byte_array(message));
+}
+
+#[test]
+fn cbc_mac_truncated_tag() {
+	let message: Vec<u8> = (0x30..0x58).collect();
+	let full: CbcMac<ISO9797M2> = CbcMac::algorithm1(Speck_128_128::new(&TEST_KEY1), 16);
+	let short: CbcMac<ISO9797M2> = CbcMac::algorithm1(Speck_128_128::new(&TEST_KEY1), 8);
+	let tag: Vec<u8> = short.cbc_mac_byte_array(&message);
+	assert_eq!(tag.as_slice(), &full.cbc_mac_byte_array(&message)[..8]);
+	assert!(short.verify(&message, &tag));
+	assert!(!short.verify(&message, &tag[..7]));
+	assert_eq!(short.algorithm(), MacAlgorithm::Algorithm1);
+}
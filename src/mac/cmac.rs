@@ -0,0 +1,205 @@
+#![allow(unused_parens)]
+
+// CMAC (NIST SP 800-38B), also known as OMAC1, for 128-bit block ciphers.
+// The same engine is used inside EAX and SIV.
+// The last block must be kept until finalize, because it's masked with a subkey.
+
+use block128::Block128;
+use block_cipher::BlockCipher;
+use speck_128_128::Speck_128_128;
+use util;
+use std::cmp;
+
+const BLOCK_LEN: usize = 16;
+// SP 800-38B doesn't recommend tags shorter than 64 bits, but allows them for some protocols
+const MIN_TAG_LEN: usize = 4;
+
+// K1 = dbl(E(0)), K2 = dbl(K1)
+pub(crate) struct Subkeys {
+	k1: [u8; BLOCK_LEN],
+	k2: [u8; BLOCK_LEN],
+}
+
+impl Subkeys {
+	pub(crate) fn new<BC: BlockCipher>(block_cipher: &BC) -> Subkeys {
+		let mut l: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		block_cipher.encrypt_block(&mut l);
+		let k1: Block128 = Block128::from(u128::from_be_bytes(l)).double();
+		let k2: Block128 = k1.double();
+		Subkeys {k1: k1.to_bytes(), k2: k2.to_bytes()}
+	}
+}
+
+#[derive(Clone)]
+pub(crate) struct OmacState {
+	mac: [u8; BLOCK_LEN],
+	buffer: [u8; BLOCK_LEN],
+	buffer_len: usize,
+}
+
+impl OmacState {
+	pub(crate) fn new() -> OmacState {
+		OmacState {mac: [0; BLOCK_LEN], buffer: [0; BLOCK_LEN], buffer_len: 0}
+	}
+
+	// OMAC^t from EAX: the message is prefixed with a block holding t
+	pub(crate) fn with_tweak(tweak: u8) -> OmacState {
+		let mut state: OmacState = OmacState::new();
+		state.buffer[BLOCK_LEN - 1] = tweak;
+		state.buffer_len = BLOCK_LEN;
+		state
+	}
+
+	pub(crate) fn update<BC: BlockCipher>(&mut self, block_cipher: &BC, data: &[u8]) {
+		let mut rest: &[u8] = data;
+		while (!rest.is_empty()) {
+			if (self.buffer_len == BLOCK_LEN) {
+				util::xor_in_place(&mut self.mac, &self.buffer);
+				block_cipher.encrypt_block(&mut self.mac);
+				self.buffer_len = 0;
+			}
+			let chunk_len: usize = cmp::min(BLOCK_LEN - self.buffer_len, rest.len());
+			self.buffer[self.buffer_len .. self.buffer_len + chunk_len].copy_from_slice(&rest[..chunk_len]);
+			self.buffer_len += chunk_len;
+			rest = &rest[chunk_len..];
+		}
+	}
+
+	// Full last block is masked with K1, partial one is padded with 10..0 and masked with K2
+	pub(crate) fn finalize<BC: BlockCipher>(&self, block_cipher: &BC, subkeys: &Subkeys) -> [u8; BLOCK_LEN] {
+		let mut last_block: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		last_block[..self.buffer_len].copy_from_slice(&self.buffer[..self.buffer_len]);
+		if (self.buffer_len == BLOCK_LEN) {
+			util::xor_in_place(&mut last_block, &subkeys.k1);
+		} else {
+			last_block[self.buffer_len] = 0x80;
+			util::xor_in_place(&mut last_block, &subkeys.k2);
+		}
+
+		let mut mac: [u8; BLOCK_LEN] = self.mac;
+		util::xor_in_place(&mut mac, &last_block);
+		block_cipher.encrypt_block(&mut mac);
+		mac
+	}
+}
+
+pub struct CMAC <BC = Speck_128_128> {
+	block_cipher: BC,
+	subkeys: Subkeys,
+	tag_len: usize,
+	state: OmacState,
+}
+
+impl CMAC {
+	pub fn new<U: Into<Block128>>(key: U, tag_len: usize) -> CMAC {
+		CMAC::with_cipher(Speck_128_128::new(key.into()), tag_len)
+	}
+}
+
+impl <BC: BlockCipher> CMAC <BC> {
+	pub fn with_cipher(block_cipher: BC, tag_len: usize) -> CMAC<BC> {
+		assert!(BC::BLOCK_SIZE == BLOCK_LEN, "CMAC is defined for 128-bit blocks only!");
+		assert!((MIN_TAG_LEN..=BLOCK_LEN).contains(&tag_len), "Tag must have {0}..{1} bytes!", MIN_TAG_LEN, BLOCK_LEN);
+		let subkeys: Subkeys = Subkeys::new(&block_cipher);
+		CMAC {block_cipher, subkeys, tag_len, state: OmacState::new()}
+	}
+
+	pub fn update(&mut self, data: &[u8]) {
+		self.state.update(&self.block_cipher, data);
+	}
+
+	// Returns the tag and resets the state, so the next message can be authenticated with the same key
+	pub fn finalize(&mut self) -> Vec<u8> {
+		let mac: [u8; BLOCK_LEN] = self.state.finalize(&self.block_cipher, &self.subkeys);
+		self.reset();
+		mac[..self.tag_len].to_vec()
+	}
+
+	// Time doesn't depend on the tag value. Resets the state as well.
+	pub fn verify(&mut self, tag: &[u8]) -> bool {
+		let expected_tag: Vec<u8> = self.finalize();
+		util::constant_time_eq(&expected_tag, tag)
+	}
+
+	pub fn reset(&mut self) {
+		self.state = OmacState::new();
+	}
+
+	pub fn cmac_byte_array(&mut self, message: &[u8]) -> Vec<u8> {
+		self.reset();
+		self.update(message);
+		self.finalize()
+	}
+}
+
+#[cfg(test)]
+const TEST_KEY: [u64; 2] = [0xa6d2ae2816157e2b, 0x3c4fcf098815f7ab];
+#[cfg(test)]
+const TEST_MESSAGE: [u8; 64] = [
+	0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a,
+	0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51,
+	0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef,
+	0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10];
+
+#[test]
+fn cmac_works1() {
+	//Key and messages of the SP 800-38B examples
+	let expected: [[u8; 16]; 4] = [
+		[0x61, 0x5c, 0x7a, 0x03, 0x8f, 0xb9, 0x1d, 0xf9, 0xfd, 0x33, 0x40, 0x26, 0x1d, 0x2f, 0x61, 0xbe],
+		[0x51, 0xe8, 0x2a, 0x52, 0x6f, 0x24, 0x93, 0x30, 0x2c, 0xd8, 0x0a, 0x5b, 0xf0, 0x2f, 0xe3, 0x03],
+		[0x5b, 0x27, 0xad, 0xa9, 0xde, 0x3d, 0xe3, 0xd9, 0xdf, 0x96, 0x61, 0xdf, 0xc6, 0x6c, 0xa3, 0x2a],
+		[0x99, 0x9f, 0xd2, 0xa1, 0x74, 0xb4, 0x8f, 0x5e, 0xaf, 0x6b, 0x4a, 0x9c, 0xe5, 0x20, 0xed, 0x47]];
+
+	let mut c: CMAC = CMAC::new(&TEST_KEY, 16);
+	for (i, &len) in [0, 16, 40, 64].iter().enumerate() {
+		assert_eq!(c.cmac_byte_array(&TEST_MESSAGE[..len]).as_slice(), &expected[i][..]);
+	}
+}
+
+#[test]
+fn cmac_incremental() {
+	let mut c: CMAC = CMAC::new(&TEST_KEY, 16);
+	for len in 0 .. TEST_MESSAGE.len() {
+		let expected: Vec<u8> = c.cmac_byte_array(&TEST_MESSAGE[..len]);
+		for chunk_len in 1..18 {
+			for chunk in TEST_MESSAGE[..len].chunks(chunk_len) {
+				c.update(chunk);
+			}
+			assert_eq!(c.finalize(), expected);
+		}
+	}
+}
+
+#[test]
+fn cmac_truncated_tag() {
+	let full: Vec<u8> = CMAC::new(&TEST_KEY, 16).cmac_byte_array(&TEST_MESSAGE[..40]);
+	for tag_len in 4..16 {
+		let mut c: CMAC = CMAC::new(&TEST_KEY, tag_len);
+		assert_eq!(c.cmac_byte_array(&TEST_MESSAGE[..40]).as_slice(), &full[..tag_len]);
+	}
+}
+
+#[test]
+#[should_panic]
+fn cmac_short_tag() {
+	let _c: CMAC = CMAC::new(&TEST_KEY, 3);
+}
+
+#[test]
+fn cmac_verify() {
+	let mut c: CMAC = CMAC::new(&TEST_KEY, 8);
+	let tag: Vec<u8> = c.cmac_byte_array(&TEST_MESSAGE);
+
+	c.update(&TEST_MESSAGE);
+	assert!(c.verify(&tag));
+
+	c.update(&TEST_MESSAGE[1..]);
+	assert!(!c.verify(&tag));
+
+	let mut wrong_tag: Vec<u8> = tag.clone();
+	wrong_tag[7] ^= 0x01;
+	c.update(&TEST_MESSAGE);
+	assert!(!c.verify(&wrong_tag));
+	c.update(&TEST_MESSAGE);
+	assert!(!c.verify(&tag[..7]));
+}
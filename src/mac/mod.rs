@@ -0,0 +1,5 @@
+// Message authentication codes over 128-bit block ciphers
+
+pub mod cmac;
+pub mod cbc_mac;
+pub mod pmac;
@@ -0,0 +1,220 @@
+#![allow(unused_parens)]
+
+// PMAC1 (Black, Rogaway) for 128-bit block ciphers: blocks are processed independently.
+// Block i (counting from 1) is masked with the offset gamma(i) * L, where gamma is a Gray code,
+// and the encrypted blocks are simply XORed, so they can be added in any order or in parallel.
+// The last block is not encrypted: a full one is masked with L / x, a partial one is padded with 10..0.
+
+use block128::Block128;
+use block_cipher::BlockCipher;
+use speck_128_128::Speck_128_128;
+use util;
+
+const BLOCK_LEN: usize = 16;
+// L(i) = L * x^i, Gray code of a 64-bit index has at most 64 bits
+const L_TABLE_LEN: usize = 64;
+
+pub struct PMAC <BC = Speck_128_128> {
+	block_cipher: BC,
+	l: Vec<[u8; BLOCK_LEN]>,
+	l_inverse: [u8; BLOCK_LEN],
+	tag_len: usize,
+	sum: [u8; BLOCK_LEN],
+}
+
+impl PMAC {
+	pub fn new<U: Into<Block128>>(key: U, tag_len: usize) -> PMAC {
+		PMAC::with_cipher(Speck_128_128::new(key.into()), tag_len)
+	}
+}
+
+impl <BC: BlockCipher> PMAC <BC> {
+	pub fn with_cipher(block_cipher: BC, tag_len: usize) -> PMAC<BC> {
+		assert!(BC::BLOCK_SIZE == BLOCK_LEN, "PMAC is defined for 128-bit blocks only!");
+		assert!(tag_len > 0 && tag_len <= BLOCK_LEN, "Tag must have 1..{0} bytes!", BLOCK_LEN);
+
+		let mut l0: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		block_cipher.encrypt_block(&mut l0);
+		let mut current: Block128 = Block128::from(u128::from_be_bytes(l0));
+		let l_inverse: [u8; BLOCK_LEN] = current.halve().to_bytes();
+		let mut l: Vec<[u8; BLOCK_LEN]> = Vec::with_capacity(L_TABLE_LEN);
+		for _ in 0..L_TABLE_LEN {
+			l.push(current.to_bytes());
+			current = current.double();
+		}
+
+		PMAC {block_cipher, l, l_inverse, tag_len, sum: [0; BLOCK_LEN]}
+	}
+
+	// Sum of the encrypted full blocks first_index, first_index + 1, ...
+	// It needs only a shared reference, so parts of a buffer can be processed in different threads.
+	pub fn blocks_sum(&self, first_index: u64, blocks: &[u8]) -> [u8; BLOCK_LEN] {
+		assert!(first_index > 0, "Blocks are numbered from 1!");
+		assert!(blocks.len().is_multiple_of(BLOCK_LEN), "Only full blocks can be added!");
+
+		let mut sum: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		let mut offset: [u8; BLOCK_LEN] = self.offset(first_index);
+		for (i, chunk) in blocks.chunks(BLOCK_LEN).enumerate() {
+			if (i > 0) {
+				util::xor_in_place(&mut offset, &self.l[(first_index + i as u64).trailing_zeros() as usize]);
+			}
+			let mut block: [u8; BLOCK_LEN] = offset;
+			util::xor_in_place(&mut block, chunk);
+			self.block_cipher.encrypt_block(&mut block);
+			util::xor_in_place(&mut sum, &block);
+		}
+		sum
+	}
+
+	pub fn add_sum(&mut self, sum: &[u8; BLOCK_LEN]) {
+		util::xor_in_place(&mut self.sum, sum);
+	}
+
+	// Any block except the last one, in any order
+	pub fn update_block(&mut self, index: u64, block: &[u8]) {
+		assert!(block.len() == BLOCK_LEN, "Block must have {0} bytes!", BLOCK_LEN);
+		let sum: [u8; BLOCK_LEN] = self.blocks_sum(index, block);
+		self.add_sum(&sum);
+	}
+
+	// The last block has 1..16 bytes, it's empty only for an empty message.
+	// Returns the tag and resets the state.
+	pub fn finalize(&mut self, last_block: &[u8]) -> Vec<u8> {
+		assert!(last_block.len() <= BLOCK_LEN, "Last block can't have more than {0} bytes!", BLOCK_LEN);
+
+		let mut tag: [u8; BLOCK_LEN] = self.sum;
+		util::xor_in_place(&mut tag, last_block);
+		if (last_block.len() == BLOCK_LEN) {
+			util::xor_in_place(&mut tag, &self.l_inverse);
+		} else {
+			tag[last_block.len()] ^= 0x80;
+		}
+		self.block_cipher.encrypt_block(&mut tag);
+
+		self.reset();
+		tag[..self.tag_len].to_vec()
+	}
+
+	// Time doesn't depend on the tag value. Resets the state as well.
+	pub fn verify(&mut self, last_block: &[u8], tag: &[u8]) -> bool {
+		let expected_tag: Vec<u8> = self.finalize(last_block);
+		util::constant_time_eq(&expected_tag, tag)
+	}
+
+	pub fn reset(&mut self) {
+		self.sum = [0; BLOCK_LEN];
+	}
+
+	pub fn pmac_byte_array(&mut self, message: &[u8]) -> Vec<u8> {
+		self.reset();
+		let last_len: usize = match message.len() % BLOCK_LEN {
+			0 if !message.is_empty() => BLOCK_LEN,
+			rest                     => rest,
+		};
+		let (blocks, last_block) = message.split_at(message.len() - last_len);
+		if (!blocks.is_empty()) {
+			let sum: [u8; BLOCK_LEN] = self.blocks_sum(1, blocks);
+			self.add_sum(&sum);
+		}
+		self.finalize(last_block)
+	}
+
+	// Offset of block i is gamma(i) * L: XOR of L(j) for all set bits j of the Gray code of i
+	fn offset(&self, index: u64) -> [u8; BLOCK_LEN] {
+		let gray: u64 = index ^ (index >> 1);
+		let mut offset: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		for (j, l) in self.l.iter().enumerate() {
+			if ((gray >> j) & 1 == 1) {
+				util::xor_in_place(&mut offset, l);
+			}
+		}
+		offset
+	}
+}
+
+#[cfg(test)]
+fn test_message(len: usize) -> Vec<u8> {
+	(0..len).map(|x| x as u8).collect()
+}
+
+#[test]
+fn pmac_works1() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let expected: [(usize, [u8; 16]); 7] = [
+		(0, [0x04, 0x16, 0x2e, 0xbf, 0x45, 0x4a, 0x90, 0xa8, 0xef, 0xeb, 0x31, 0x5c, 0x24, 0x72, 0x45, 0x8e]),
+		(3, [0xda, 0xf8, 0xdb, 0xd4, 0x59, 0x81, 0x9e, 0xac, 0x8c, 0x9c, 0xad, 0x8e, 0x9e, 0xf7, 0x35, 0xf9]),
+		(16, [0x0b, 0xf8, 0x70, 0xf3, 0xc5, 0xac, 0x2b, 0xeb, 0xfe, 0x0c, 0xa2, 0xbe, 0xe4, 0x69, 0xbd, 0x37]),
+		(20, [0x2e, 0x84, 0x91, 0x89, 0xf5, 0x9b, 0xee, 0xac, 0x9b, 0x6f, 0x99, 0x4b, 0x0f, 0x83, 0xd4, 0x7f]),
+		(32, [0x07, 0x4e, 0xdb, 0x3c, 0x2f, 0xd8, 0x49, 0xc4, 0xfe, 0x5a, 0x44, 0x98, 0xee, 0x41, 0x6d, 0x92]),
+		(34, [0x85, 0xf7, 0xdd, 0xc7, 0x62, 0x8c, 0x3f, 0x9b, 0xf0, 0xd7, 0x07, 0x98, 0x4a, 0xe4, 0x0c, 0xbe]),
+		(1000, [0x61, 0x54, 0x17, 0x61, 0x1b, 0x0b, 0x7c, 0x35, 0x35, 0x4d, 0x41, 0xe9, 0xf3, 0x71, 0x00, 0x10])];
+
+	let mut c: PMAC = PMAC::new(&key, 16);
+	for &(len, ref tag) in expected.iter() {
+		assert_eq!(c.pmac_byte_array(&test_message(len)).as_slice(), &tag[..]);
+	}
+}
+
+#[test]
+fn pmac_out_of_order() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let message: Vec<u8> = test_message(1000);
+	let mut c: PMAC = PMAC::new(&key, 16);
+	let expected: Vec<u8> = c.pmac_byte_array(&message);
+
+	//Blocks 1..62 in reverse order, the last 8 bytes are the last block
+	for (i, block) in message[..992].chunks(16).enumerate().rev() {
+		c.update_block(i as u64 + 1, block);
+	}
+	assert_eq!(c.finalize(&message[992..]), expected);
+
+	//Each offset computed directly gives the same as the incremental computation
+	let sum: [u8; 16] = c.blocks_sum(1, &message[..992]);
+	let mut sum_by_blocks: [u8; 16] = [0; 16];
+	for (i, block) in message[..992].chunks(16).enumerate() {
+		util::xor_in_place(&mut sum_by_blocks, &c.blocks_sum(i as u64 + 1, block));
+	}
+	assert_eq!(sum, sum_by_blocks);
+}
+
+#[test]
+fn pmac_parallel() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let message: Vec<u8> = test_message(1000);
+	let mut c: PMAC = PMAC::new(&key, 16);
+	let expected: Vec<u8> = c.pmac_byte_array(&message);
+
+	//Four threads, 16 blocks each (the last one has 14 blocks)
+	let shared: &PMAC = &c;
+	let sums: Vec<[u8; 16]> = ::std::thread::scope(|s| {
+		let mut handles = Vec::new();
+		for (i, part) in message[..992].chunks(256).enumerate() {
+			handles.push(s.spawn(move || shared.blocks_sum(i as u64 * 16 + 1, part)));
+		}
+		handles.into_iter().map(|h| h.join().unwrap()).collect()
+	});
+	for sum in sums.iter() {
+		c.add_sum(sum);
+	}
+	assert_eq!(c.finalize(&message[992..]), expected);
+}
+
+#[test]
+fn pmac_verify() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let message: Vec<u8> = test_message(32);
+
+	let mut c: PMAC = PMAC::new(&key, 8);
+	let tag: Vec<u8> = c.pmac_byte_array(&message);
+	assert_eq!(tag.len(), 8);
+
+	c.update_block(1, &message[..16]);
+	assert!(c.verify(&message[16..], &tag));
+
+	c.update_block(2, &message[..16]);
+	assert!(!c.verify(&message[16..], &tag));
+
+	//A full last block is different from a padded one
+	c.update_block(1, &message[..16]);
+	assert!(!c.verify(&message[16..31], &tag));
+}
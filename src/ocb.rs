@@ -0,0 +1,274 @@
+#![allow(unused_parens)]
+
+// OCB3 (RFC 7253) for 128-bit block ciphers: one block cipher call per block of data.
+// Nonce has up to 15 bytes, tag has 1..16 bytes and is appended to ciphertext.
+
+use block128::Block128;
+use block_cipher::BlockCipher;
+use speck_128_128::Speck_128_128;
+use util;
+
+const BLOCK_LEN: usize = 16;
+// L_i for i = ntz(block number), enough for any message that fits in memory
+const L_TABLE_LEN: usize = 64;
+
+#[derive(Debug, PartialEq)]
+pub enum OcbError {
+	WrongNonce,
+	WrongInput,
+	AuthenticationFailed,
+}
+
+pub struct OCB <BC = Speck_128_128> {
+	block_cipher: BC,
+	l_star: [u8; BLOCK_LEN],
+	l_dollar: [u8; BLOCK_LEN],
+	l: Vec<[u8; BLOCK_LEN]>,
+	tag_len: usize,
+}
+
+impl OCB {
+	pub fn new<U: Into<Block128>>(key: U, tag_len: usize) -> OCB {
+		OCB::with_cipher(Speck_128_128::new(key.into()), tag_len)
+	}
+}
+
+impl <BC: BlockCipher> OCB <BC> {
+	pub fn with_cipher(block_cipher: BC, tag_len: usize) -> OCB<BC> {
+		assert!(BC::BLOCK_SIZE == BLOCK_LEN, "OCB is defined for 128-bit blocks only!");
+		assert!(tag_len > 0 && tag_len <= BLOCK_LEN, "Tag must have 1..{0} bytes!", BLOCK_LEN);
+
+		let mut l_star: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		block_cipher.encrypt_block(&mut l_star);
+		let mut current: Block128 = Block128::from(u128::from_be_bytes(l_star)).double();
+		let l_dollar: [u8; BLOCK_LEN] = current.to_bytes();
+		let mut l: Vec<[u8; BLOCK_LEN]> = Vec::with_capacity(L_TABLE_LEN);
+		for _ in 0..L_TABLE_LEN {
+			current = current.double();
+			l.push(current.to_bytes());
+		}
+
+		OCB {block_cipher, l_star, l_dollar, l, tag_len}
+	}
+
+	pub fn seal(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, OcbError> {
+		let mut offset: [u8; BLOCK_LEN] = self.initial_offset(nonce)?;
+		let mut checksum: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		let mut ciphertext: Vec<u8> = Vec::with_capacity(plaintext.len() + self.tag_len);
+		ciphertext.extend_from_slice(plaintext);
+
+		let full_len: usize = plaintext.len() - plaintext.len() % BLOCK_LEN;
+		for (i, block) in ciphertext[..full_len].chunks_mut(BLOCK_LEN).enumerate() {
+			util::xor_in_place(&mut offset, &self.l[(i + 1).trailing_zeros() as usize]);
+			util::xor_in_place(&mut checksum, block);
+			util::xor_in_place(block, &offset);
+			self.block_cipher.encrypt_block(block);
+			util::xor_in_place(block, &offset);
+		}
+
+		if (full_len < plaintext.len()) {
+			util::xor_in_place(&mut offset, &self.l_star);
+			let mut pad: [u8; BLOCK_LEN] = offset;
+			self.block_cipher.encrypt_block(&mut pad);
+			let last_block: &mut [u8] = &mut ciphertext[full_len..];
+			util::xor_in_place(&mut checksum, last_block);
+			checksum[last_block.len()] ^= 0x80;
+			util::xor_in_place(last_block, &pad);
+		}
+
+		let tag: [u8; BLOCK_LEN] = self.tag(&checksum, &offset, aad);
+		ciphertext.extend_from_slice(&tag[..self.tag_len]);
+		Ok(ciphertext)
+	}
+
+	pub fn open(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, OcbError> {
+		if (ciphertext.len() < self.tag_len) { return Err(OcbError::WrongInput) };
+		let (encrypted, tag) = ciphertext.split_at(ciphertext.len() - self.tag_len);
+
+		let mut offset: [u8; BLOCK_LEN] = self.initial_offset(nonce)?;
+		let mut checksum: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		let mut decrypted: Vec<u8> = encrypted.to_vec();
+
+		let full_len: usize = encrypted.len() - encrypted.len() % BLOCK_LEN;
+		for (i, block) in decrypted[..full_len].chunks_mut(BLOCK_LEN).enumerate() {
+			util::xor_in_place(&mut offset, &self.l[(i + 1).trailing_zeros() as usize]);
+			util::xor_in_place(block, &offset);
+			self.block_cipher.decrypt_block(block);
+			util::xor_in_place(block, &offset);
+			util::xor_in_place(&mut checksum, block);
+		}
+
+		if (full_len < encrypted.len()) {
+			util::xor_in_place(&mut offset, &self.l_star);
+			let mut pad: [u8; BLOCK_LEN] = offset;
+			self.block_cipher.encrypt_block(&mut pad);
+			let last_block: &mut [u8] = &mut decrypted[full_len..];
+			util::xor_in_place(last_block, &pad);
+			util::xor_in_place(&mut checksum, last_block);
+			checksum[last_block.len()] ^= 0x80;
+		}
+
+		let expected_tag: [u8; BLOCK_LEN] = self.tag(&checksum, &offset, aad);
+		if (!util::constant_time_eq(&expected_tag[..self.tag_len], tag)) {
+			util::wipe(&mut decrypted);
+			return Err(OcbError::AuthenticationFailed);
+		}
+		Ok(decrypted)
+	}
+
+	// Nonce block is TAGLEN mod 128 in 7 bits, zeros, 1 and the nonce.
+	// Its last 6 bits select a 128-bit window of Stretch = Ktop || (Ktop[1..64] ^ Ktop[9..72]).
+	fn initial_offset(&self, nonce: &[u8]) -> Result<[u8; BLOCK_LEN], OcbError> {
+		if (nonce.len() >= BLOCK_LEN) { return Err(OcbError::WrongNonce) };
+
+		let mut nonce_block: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		nonce_block[0] = (((self.tag_len * 8) % 128) << 1) as u8;
+		nonce_block[BLOCK_LEN - nonce.len() - 1] |= 1;
+		nonce_block[BLOCK_LEN - nonce.len() ..].copy_from_slice(nonce);
+
+		let bottom: u32 = (nonce_block[BLOCK_LEN - 1] & 0x3F) as u32;
+		nonce_block[BLOCK_LEN - 1] &= 0xC0;
+		self.block_cipher.encrypt_block(&mut nonce_block);
+
+		let ktop: u128 = u128::from_be_bytes(nonce_block);
+		let stretch: u64 = ((ktop >> 64) ^ (ktop >> 56)) as u64;
+		let offset: u128 = if (bottom == 0) { ktop } else { (ktop << bottom) | ((stretch >> (64 - bottom)) as u128) };
+		Ok(offset.to_be_bytes())
+	}
+
+	fn tag(&self, checksum: &[u8; BLOCK_LEN], offset: &[u8; BLOCK_LEN], aad: &[u8]) -> [u8; BLOCK_LEN] {
+		let mut tag: [u8; BLOCK_LEN] = *checksum;
+		util::xor_in_place(&mut tag, offset);
+		util::xor_in_place(&mut tag, &self.l_dollar);
+		self.block_cipher.encrypt_block(&mut tag);
+		util::xor_in_place(&mut tag, &self.hash(aad));
+		tag
+	}
+
+	fn hash(&self, aad: &[u8]) -> [u8; BLOCK_LEN] {
+		let mut sum: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		let mut offset: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		for (i, chunk) in aad.chunks(BLOCK_LEN).enumerate() {
+			let mut block: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+			block[..chunk.len()].copy_from_slice(chunk);
+			if (chunk.len() == BLOCK_LEN) {
+				util::xor_in_place(&mut offset, &self.l[(i + 1).trailing_zeros() as usize]);
+			} else {
+				util::xor_in_place(&mut offset, &self.l_star);
+				block[chunk.len()] = 0x80;
+			}
+			util::xor_in_place(&mut block, &offset);
+			self.block_cipher.encrypt_block(&mut block);
+			util::xor_in_place(&mut sum, &block);
+		}
+		sum
+	}
+}
+
+#[test]
+fn ocb_works1() {
+	//Inputs follow the RFC 7253 sample results: the last nonce byte is the data length and
+	//the associated data equals the plaintext. There are no Speck samples, these are regression values.
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let mut nonce: [u8; 12] = [0xbb, 0xaa, 0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00];
+	let expected: [&[u8]; 5] = [
+		&[0x94, 0x5d, 0xc7, 0x98, 0xff, 0x48, 0xf6, 0x3e, 0x57, 0x37, 0xed, 0xc7, 0xc8, 0xe0, 0x3c, 0x54],
+		&[
+		0x8c, 0x97, 0x6c, 0x5a, 0x8e, 0xb3, 0xdd, 0x51, 0x26, 0x5a, 0x6e, 0xf5, 0x36, 0x66, 0x07, 0x8d,
+		0xc8, 0x96, 0x4f, 0x4b, 0xd7, 0xa8, 0x5d, 0xac],
+		&[
+		0x8e, 0x21, 0x86, 0x5b, 0x60, 0x44, 0x6a, 0xc4, 0x0e, 0x72, 0x06, 0xff, 0xa3, 0xff, 0xfa, 0xd3,
+		0x82, 0xa9, 0xf4, 0x66, 0x2c, 0x0b, 0x02, 0x7b, 0xb0, 0xa2, 0x78, 0xb1, 0x0f, 0xd7, 0x53, 0x9e],
+		&[
+		0x56, 0x4f, 0x07, 0x21, 0x79, 0x61, 0xbd, 0x52, 0x98, 0x5f, 0x0a, 0x1d, 0x9e, 0xa8, 0xdf, 0x37,
+		0xc1, 0x35, 0xd6, 0x06, 0x16, 0x00, 0x71, 0x1f, 0x5b, 0x2f, 0xce, 0xea, 0x3c, 0xeb, 0x32, 0xcc,
+		0x23, 0xd5, 0x95, 0x1d, 0x28, 0x2e, 0x38, 0x73],
+		&[
+		0x3a, 0xfc, 0xed, 0xc1, 0x87, 0xef, 0x37, 0x3e, 0x10, 0x90, 0x31, 0xac, 0x8c, 0x7b, 0x39, 0x8e,
+		0xc0, 0x3c, 0x5b, 0xd4, 0x08, 0x72, 0xd9, 0x9e, 0x66, 0xb1, 0x41, 0x7e, 0xc5, 0x07, 0x3c, 0xa2,
+		0xa5, 0x49, 0x89, 0xdb, 0x42, 0x58, 0xe5, 0x1e, 0x1b, 0x28, 0xc7, 0xb8, 0x67, 0x32, 0x9b, 0xf4,
+		0xda, 0xf5, 0xb6, 0x70, 0x5b, 0x2d, 0x21, 0xb2]];
+
+	let c: OCB = OCB::new(&key, 16);
+	for (i, &len) in [0, 8, 16, 24, 40].iter().enumerate() {
+		nonce[11] = len;
+		let data: Vec<u8> = (0..len).collect();
+		let ciphertext: Vec<u8> = c.seal(&nonce, &data, &data).unwrap();
+		assert_eq!(ciphertext.as_slice(), expected[i]);
+		assert_eq!(c.open(&nonce, &data, &ciphertext).unwrap(), data);
+	}
+}
+
+#[test]
+fn ocb_works2() {
+	//Key, nonce and data of the RFC 7253 sample with 96-bit tag
+	let key: [u64; 2] = [0x08090a0b0c0d0e0f, 0x0001020304050607];
+	let nonce: [u8; 12] = [0xbb, 0xaa, 0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x0d];
+	let data: Vec<u8> = (0..40).collect();
+	let expected: [u8; 52] = [
+		0xe5, 0x74, 0x75, 0x42, 0x11, 0x8a, 0xb1, 0xed, 0x0b, 0x6b, 0x18, 0x28, 0x5f, 0x4c, 0x44, 0xc1,
+		0xa5, 0x72, 0x47, 0x6b, 0x21, 0x13, 0x95, 0x0e, 0x4b, 0xba, 0x1a, 0xc9, 0x2c, 0xdf, 0x77, 0x5c,
+		0xb1, 0xe2, 0x82, 0x4a, 0xe3, 0x3a, 0x4c, 0xab, 0x4c, 0x83, 0x89, 0xf0, 0xfe, 0xa6, 0x7f, 0x08,
+		0x3f, 0x6d, 0x32, 0x64];
+
+	let c: OCB = OCB::new(&key, 12);
+	let ciphertext: Vec<u8> = c.seal(&nonce, &data, &data).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.open(&nonce, &data, &ciphertext).unwrap(), data);
+}
+
+#[test]
+fn ocb_works3() {
+	//One-byte nonce: bottom bits are not zero, so Stretch is used
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let plaintext: Vec<u8> = (0..16).collect();
+	let expected: [u8; 32] = [
+		0x40, 0x20, 0x43, 0xc9, 0x47, 0xd7, 0x4a, 0xb1, 0x1d, 0xc2, 0x70, 0x36, 0xb7, 0x34, 0x89, 0x2c,
+		0x46, 0xcb, 0x62, 0xdc, 0xea, 0x51, 0x27, 0xc8, 0x4b, 0xcf, 0x3a, 0xbb, 0x53, 0x96, 0x6c, 0x8f];
+
+	let c: OCB = OCB::new(&key, 16);
+	let ciphertext: Vec<u8> = c.seal(&[0x01], &[], &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.open(&[0x01], &[], &ciphertext).unwrap(), plaintext);
+}
+
+#[test]
+fn ocb_authentication_fails() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let nonce: [u8; 12] = [0xbb, 0xaa, 0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00];
+	let plaintext: Vec<u8> = (0..70).collect();
+
+	let c: OCB = OCB::new(&key, 16);
+	let ciphertext: Vec<u8> = c.seal(&nonce, b"header", &plaintext).unwrap();
+	util::assert_bit_flips_detected(&ciphertext, |x| c.open(&nonce, b"header", x), OcbError::AuthenticationFailed);
+	assert_eq!(c.open(&nonce, b"Header", &ciphertext), Err(OcbError::AuthenticationFailed));
+	assert_eq!(c.open(&nonce[1..], b"header", &ciphertext), Err(OcbError::AuthenticationFailed));
+	assert_eq!(c.open(&nonce, b"header", &ciphertext[..15]), Err(OcbError::WrongInput));
+	assert_eq!(c.seal(&[0; 16], b"header", &plaintext), Err(OcbError::WrongNonce));
+}
+
+#[test]
+fn ocb_short_tags() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let nonce: [u8; 12] = [0xbb, 0xaa, 0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00];
+	let plaintext: Vec<u8> = (0..70).collect();
+
+	let c16: OCB = OCB::new(&key, 16);
+	let c8: OCB = OCB::new(&key, 8);
+	let ciphertext16: Vec<u8> = c16.seal(&nonce, b"header", &plaintext).unwrap();
+	let ciphertext8: Vec<u8> = c8.seal(&nonce, b"header", &plaintext).unwrap();
+	assert_eq!(ciphertext8.len(), 78);
+	assert_eq!(c8.open(&nonce, b"header", &ciphertext8).unwrap(), plaintext);
+
+	//Tag length is encoded in the nonce block, so it changes the whole ciphertext
+	//and a cut 16-byte tag is not a valid 8-byte one
+	assert!(ciphertext8[..70] != ciphertext16[..70]);
+	assert_eq!(c8.open(&nonce, b"header", &ciphertext16[..78]), Err(OcbError::AuthenticationFailed));
+
+	//The shortest tag is one byte, and the message still has to hold it
+	let c1: OCB = OCB::new(&key, 1);
+	let ciphertext1: Vec<u8> = c1.seal(&nonce, b"header", &plaintext).unwrap();
+	assert_eq!(c1.open(&nonce, b"header", &ciphertext1).unwrap(), plaintext);
+	assert_eq!(c1.open(&nonce, b"header", &[]), Err(OcbError::WrongInput));
+	assert_eq!(c8.open(&nonce, b"header", &ciphertext8[..7]), Err(OcbError::WrongInput));
+}
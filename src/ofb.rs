@@ -0,0 +1,96 @@
+#![allow(unused_parens)]
+
+use block128::Block128;
+use block_cipher::BlockCipher;
+use speck_128_128::Speck_128_128;
+use util;
+use std::cmp;
+#[cfg(test)]
+use speck::Speck_64_128;
+
+// Output feedback: the IV is encrypted again and again, blocks of it are the keystream.
+// No padding is needed, data can be passed in pieces of any size.
+pub struct OFB <BC = Speck_128_128> {
+	register: Vec<u8>,
+	used: usize,
+	block_cipher: BC,
+}
+
+impl OFB {
+	pub fn new<U: Into<Block128>>(iv: &[u8], key: U) -> OFB {
+		OFB::with_cipher(iv, Speck_128_128::new(key.into()))
+	}
+}
+
+impl <BC: BlockCipher> OFB <BC> {
+	pub fn with_cipher(iv: &[u8], block_cipher: BC) -> OFB<BC> {
+		assert!(iv.len() == BC::BLOCK_SIZE, "IV must have {0} bytes!", BC::BLOCK_SIZE);
+		//All of the IV is "used", so the first call encrypts it
+		OFB {register: iv.to_vec(), used: BC::BLOCK_SIZE, block_cipher}
+	}
+
+	pub fn apply_keystream(&mut self, data: &mut [u8]) {
+		let mut done: usize = 0;
+		while (done < data.len()) {
+			if (self.used == BC::BLOCK_SIZE) {
+				self.block_cipher.encrypt_block(&mut self.register);
+				self.used = 0;
+			}
+			let chunk_len: usize = cmp::min(BC::BLOCK_SIZE - self.used, data.len() - done);
+			util::xor_in_place(&mut data[done .. done + chunk_len], &self.register[self.used .. self.used + chunk_len]);
+
+			done += chunk_len;
+			self.used += chunk_len;
+		}
+	}
+
+	pub fn ofb_encrypt_byte_array(&mut self, plaintext: &[u8]) -> Vec<u8> {
+		let mut ciphertext: Vec<u8> = plaintext.to_vec();
+		self.apply_keystream(&mut ciphertext);
+		ciphertext
+	}
+
+	pub fn ofb_decrypt_byte_array(&mut self, ciphertext: &[u8]) -> Vec<u8> {
+		self.ofb_encrypt_byte_array(ciphertext)
+	}
+}
+
+#[test]
+fn ofb_works1() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let iv: [u8; 16]  = [0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xab, 0xac, 0xad, 0xae, 0xaf];
+	let plaintext: [u8; 35] = [
+		0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x3b, 0x3c, 0x3d, 0x3e, 0x3f,
+		0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f,
+		0x50, 0x51, 0x52];
+	let expected: [u8; 35] = [
+		0x8c, 0x44, 0xf8, 0x63, 0xf5, 0x88, 0x74, 0x24, 0x9c, 0xc7, 0x06, 0x47, 0xf1, 0x8a, 0x22, 0xb8,
+		0x0f, 0x1a, 0xb1, 0xd3, 0x07, 0xe5, 0xec, 0x14, 0xd2, 0x27, 0x88, 0xe3, 0x0c, 0x73, 0x58, 0x3c,
+		0x13, 0x3d, 0xc5];
+
+	let mut o: OFB = OFB::new(&iv, &key);
+	let ciphertext: Vec<u8> = o.ofb_encrypt_byte_array(&plaintext);
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+
+	let mut o2: OFB = OFB::new(&iv, &key);
+	let decrypted: Vec<u8> = o2.ofb_decrypt_byte_array(&ciphertext);
+	assert_eq!(decrypted.as_slice(), &plaintext[..]);
+}
+
+#[test]
+fn ofb_works2() {
+	let key: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x08, 0x09, 0x0a, 0x0b, 0x10, 0x11, 0x12, 0x13, 0x18, 0x19, 0x1a, 0x1b];
+	let iv: [u8; 8]   = [0xD2, 0xC4, 0xB7, 0xD9, 0x6C, 0x49, 0x16, 0x0E];
+	let plaintext: Vec<u8> = (0..100).collect();
+
+	let mut o1: OFB<Speck_64_128> = OFB::with_cipher(&iv, Speck_64_128::from_key_bytes(&key));
+	let ciphertext: Vec<u8> = o1.ofb_encrypt_byte_array(&plaintext);
+
+	//Byte by byte, as it comes from a serial link
+	let mut o2: OFB<Speck_64_128> = OFB::with_cipher(&iv, Speck_64_128::from_key_bytes(&key));
+	let mut decrypted: Vec<u8> = ciphertext.clone();
+	for chunk in decrypted.chunks_mut(1) {
+		o2.apply_keystream(chunk);
+	}
+	assert_eq!(decrypted, plaintext);
+}
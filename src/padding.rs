@@ -8,3 +8,9 @@ pub trait PaddingGenerator {
 	fn set_padding (plaintext: &[u8], padding: &mut[u8], block_len: usize);
 	fn remove_padding (ciphertext: &[u8], block_len: usize) -> Result<usize, PaddingError>;
 }
+
+// Padding of a message before MAC computation, it's never removed,
+// so it may add nothing or prepend data. Result length is a multiple of block_len.
+pub trait MacPaddingGenerator {
+	fn pad_message (message: &[u8], block_len: usize) -> Vec<u8>;
+}
@@ -0,0 +1,152 @@
+#![allow(unused_parens)]
+
+// Propagating CBC (Kerberos v4 style): both previous plaintext and ciphertext are mixed into the next block,
+// so a damaged ciphertext block garbles all of the following plaintext.
+
+use block128;
+use block128::Block128;
+use block_cipher::{BlockCipher, CipherErrors};
+use speck_128_128::Speck_128_128;
+use padding::PaddingGenerator;
+use util;
+use std::marker::PhantomData;
+#[cfg(test)]
+use pkcs7::PKCS7;
+#[cfg(test)]
+use speck::Speck_64_128;
+
+pub struct PCBC <PG, BC = Speck_128_128> {
+	iv: Vec<u8>,
+	block_cipher: BC,
+	padd_generator: PhantomData<PG>,
+}
+
+impl <PG: PaddingGenerator> PCBC <PG> {
+	pub fn new<U: Into<Block128>>(iv: U, key: U) -> PCBC<PG> {
+		let iv_block: Block128 = iv.into();
+		let mut iv_bytes: [u8; block128::BYTES_IN_BLOCK] = [0; block128::BYTES_IN_BLOCK];
+		util::words_to_bytes_le(&[iv_block.get_a(), iv_block.get_b()], &mut iv_bytes);
+		PCBC::with_cipher(&iv_bytes, Speck_128_128::new(key.into()))
+	}
+}
+
+impl <PG: PaddingGenerator, BC: BlockCipher> PCBC <PG, BC> {
+	pub fn with_cipher(iv: &[u8], block_cipher: BC) -> PCBC<PG, BC> {
+		assert!(iv.len() == BC::BLOCK_SIZE, "IV must have {0} bytes!", BC::BLOCK_SIZE);
+		PCBC {iv: iv.to_vec(), block_cipher, padd_generator: PhantomData::<PG> }
+	}
+
+	pub fn pcbc_encrypt_byte_array(&self, plaintext: &[u8]) -> Result<Vec<u8>, CipherErrors> {
+		if (plaintext.is_empty()) { return Err(CipherErrors::WrongInput) };
+
+		let mut last_block: Vec<u8> = vec![0; BC::BLOCK_SIZE];
+		PG::set_padding(plaintext, &mut last_block, BC::BLOCK_SIZE);
+
+		let full_blocks_len: usize = plaintext.len() - plaintext.len() % BC::BLOCK_SIZE;
+		let mut ciphertext: Vec<u8> = Vec::with_capacity(full_blocks_len + last_block.len());
+		ciphertext.extend_from_slice(&plaintext[..full_blocks_len]);
+		ciphertext.extend_from_slice(&last_block);
+
+		//feedback = P[i-1] ^ C[i-1]
+		let mut feedback: Vec<u8> = self.iv.clone();
+		let mut plain_block: Vec<u8> = vec![0; BC::BLOCK_SIZE];
+		for block in ciphertext.chunks_mut(BC::BLOCK_SIZE) {
+			plain_block.copy_from_slice(block);
+			util::xor_in_place(block, &feedback);
+			self.block_cipher.encrypt_block(block);
+			feedback.copy_from_slice(&plain_block);
+			util::xor_in_place(&mut feedback, block);
+		}
+
+		Ok(ciphertext)
+	}
+
+	pub fn pcbc_decrypt_byte_array(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherErrors> {
+		if (ciphertext.is_empty() || !ciphertext.len().is_multiple_of(BC::BLOCK_SIZE)) { return Err(CipherErrors::WrongInput) };
+
+		let mut decrypted: Vec<u8> = ciphertext.to_vec();
+
+		let mut feedback: Vec<u8> = self.iv.clone();
+		for (block, encrypted) in decrypted.chunks_mut(BC::BLOCK_SIZE).zip(ciphertext.chunks(BC::BLOCK_SIZE)) {
+			self.block_cipher.decrypt_block(block);
+			util::xor_in_place(block, &feedback);
+			feedback.copy_from_slice(block);
+			util::xor_in_place(&mut feedback, encrypted);
+		}
+
+		match PG::remove_padding(&decrypted, BC::BLOCK_SIZE) {
+			Err(_)        => Err(CipherErrors::WrongPadding),
+			Ok(plaintext_len) => {
+				decrypted.truncate(plaintext_len);
+				Ok(decrypted)
+			},
+		}
+	}
+}
+
+#[test]
+fn pcbc_works1() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let iv: [u8; 16]  = [0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xab, 0xac, 0xad, 0xae, 0xaf];
+	let plaintext: [u8; 35] = [
+		0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x3b, 0x3c, 0x3d, 0x3e, 0x3f,
+		0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f,
+		0x50, 0x51, 0x52];
+	let expected: [u8; 48] = [
+		0xf0, 0x93, 0xdd, 0xba, 0xc1, 0xc7, 0xaa, 0xa2, 0x83, 0xc3, 0x9d, 0xfe, 0x97, 0xbe, 0x92, 0x84,
+		0x32, 0xb5, 0xef, 0xdb, 0x6c, 0x1c, 0x2b, 0x83, 0xf7, 0x70, 0x12, 0x43, 0xe2, 0x66, 0x03, 0xda,
+		0x14, 0xaf, 0x22, 0xac, 0x92, 0x43, 0xd1, 0x67, 0x42, 0x3e, 0x06, 0x2d, 0x4c, 0x26, 0x51, 0x96];
+
+	let c: PCBC<PKCS7> = PCBC::with_cipher(&iv, Speck_128_128::new(&key));
+
+	let ciphertext: Vec<u8> = c.pcbc_encrypt_byte_array(&plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.pcbc_decrypt_byte_array(&ciphertext).unwrap().as_slice(), &plaintext[..]);
+}
+
+#[test]
+fn pcbc_works2() {
+	let key: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x08, 0x09, 0x0a, 0x0b, 0x10, 0x11, 0x12, 0x13, 0x18, 0x19, 0x1a, 0x1b];
+	let iv: [u8; 8]   = [0xD2, 0xC4, 0xB7, 0xD9, 0x6C, 0x49, 0x16, 0x0E];
+	let plaintext: Vec<u8> = (0..45).collect();
+
+	let c: PCBC<PKCS7, Speck_64_128> = PCBC::with_cipher(&iv, Speck_64_128::from_key_bytes(&key));
+
+	for len in 1 .. plaintext.len() {
+		let ciphertext: Vec<u8> = c.pcbc_encrypt_byte_array(&plaintext[..len]).unwrap();
+		assert_eq!(ciphertext.len(), (len / 8 + 1) * 8);
+		assert_eq!(c.pcbc_decrypt_byte_array(&ciphertext).unwrap(), &plaintext[..len]);
+	}
+
+	assert!(c.pcbc_encrypt_byte_array(&[]).is_err());
+	assert!(c.pcbc_decrypt_byte_array(&plaintext[..12]).is_err());
+}
+
+#[test]
+fn pcbc_error_propagation() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let iv: [u64; 2]  = [0xD2C4B7D96C49160E, 0x4EFE0C3E3B9FFD85];
+	let plaintext: Vec<u8> = (0..64).collect();
+
+	let c: PCBC<PKCS7> = PCBC::new(&iv, &key);
+	let ciphertext: Vec<u8> = c.pcbc_encrypt_byte_array(&plaintext).unwrap();
+
+	//Damage of one block reaches the padding at the end
+	let mut damaged: Vec<u8> = ciphertext.clone();
+	damaged[20] ^= 0x01;
+	match c.pcbc_decrypt_byte_array(&damaged) {
+		Err(CipherErrors::WrongPadding) => {},
+		_                               => panic!("Damage should propagate up to the padding!"),
+	}
+
+	//But two swapped blocks garble only themselves
+	let mut swapped: Vec<u8> = ciphertext.clone();
+	for i in 16..32 {
+		swapped.swap(i, i + 16);
+	}
+	let decrypted: Vec<u8> = c.pcbc_decrypt_byte_array(&swapped).unwrap();
+	assert_eq!(&decrypted[..16], &plaintext[..16]);
+	assert!(decrypted[16..32] != plaintext[16..32]);
+	assert!(decrypted[32..48] != plaintext[32..48]);
+	assert_eq!(&decrypted[48..], &plaintext[48..]);
+}
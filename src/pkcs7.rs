@@ -28,6 +28,10 @@ impl PaddingGenerator for PKCS7 {
 
 		let cl = ciphertext.len();
 		let padding_size: u8 = ciphertext[cl - 1];
+		//Zero is not a valid PKCS7 pad, a longer one would split outside of the last block
+		if (padding_size == 0 || padding_size as usize > block_len) {
+			return Err(PaddingError::WrongPadding);
+		}
 
 		let (text, padding) = ciphertext.split_at(cl - padding_size as usize);
 
@@ -166,3 +170,15 @@ fn pkcs7_block_misc() {
 	let r_result5 = PKCS7::remove_padding(&expected5, 13).unwrap();
 	assert_eq!(r_result5, text5.len());
 }
+
+#[test]
+fn pkcs7_wrong_padding() {
+	let text1 = [0xAA, 0xCC, 0xEE, 0xBB, 0x13, 0x02, 0x03, 0x03];
+	assert!(PKCS7::remove_padding(&text1, 8).is_err());
+
+	//Padding can't be longer than a block, or zero
+	let text2 = [0xAA, 0xCC, 0xEE, 0xBB, 0x13, 0xFF, 0x11, 0xDD, 0x50, 0x24, 0x37, 0x22, 0xF5, 0xD3, 0x00, 0x1C];
+	assert!(PKCS7::remove_padding(&text2, 8).is_err());
+	let text3 = [0xAA, 0xCC, 0xEE, 0xBB, 0x13, 0xFF, 0x11, 0x00];
+	assert!(PKCS7::remove_padding(&text3, 8).is_err());
+}
@@ -0,0 +1,302 @@
+// SIMON family, the hardware-oriented sibling of SPECK.
+// Words are kept in the same order as in speck: key[0] is k0, key[1] is k1, ...;
+// the first word of a block is y, the second is x.
+
+use block_cipher::BlockCipher;
+
+// Constant sequences z0..z4, bit i of the word is z[i].
+const Z0: u64 = 0x19c3522fb386a45f;
+const Z1: u64 = 0x16864fb8ad0c9f71;
+const Z2: u64 = 0x3369f885192c0ef5;
+const Z3: u64 = 0x3c2ce51207a635db;
+const Z4: u64 = 0x3dc94c3a046d678b;
+const Z_LEN: usize = 62;
+
+macro_rules! simon_impl {
+	($name: ident, $word: ty, $bits: expr, $key_words: expr, $rounds: expr, $z: expr) => {
+		#[allow(non_camel_case_types)]
+		pub struct $name {
+			keys_propagated: [$word; $rounds]
+		}
+
+		impl $name {
+			const MASK: $word = (!0 as $word) >> (::std::mem::size_of::<$word>() * 8 - $bits);
+			const WORD_BYTES: usize = $bits / 8;
+
+			pub fn new(key: &[$word; $key_words]) -> $name {
+				let mut key_temp: [$word; $rounds] = [0; $rounds];
+				$name::key_schedule(key, &mut key_temp);
+				$name {keys_propagated: key_temp}
+			}
+
+			//Words are read little-endian, k0 comes first: the same bytes as in speck
+			pub fn from_key_bytes(key: &[u8]) -> $name {
+				assert!(key.len() == $key_words * $name::WORD_BYTES, "Key must have {0} bytes!", $key_words * $name::WORD_BYTES);
+				let mut key_words: [$word; $key_words] = [0; $key_words];
+				for (word, chunk) in key_words.iter_mut().zip(key.chunks($name::WORD_BYTES)) {
+					*word = $name::load_word(chunk);
+				}
+				$name::new(&key_words)
+			}
+
+			#[inline]
+			fn load_word(bytes: &[u8]) -> $word {
+				bytes.iter().rev().fold(0, |acc, &x| (acc << 8) | (x as $word))
+			}
+
+			#[inline]
+			fn store_word(word: $word, bytes: &mut [u8]) {
+				for (i, x) in bytes.iter_mut().enumerate() {
+					*x = (word >> (8 * i)) as u8;
+				}
+			}
+
+			fn key_schedule(key: &[$word; $key_words], propagated: &mut [$word; $rounds]) {
+				propagated[..$key_words].copy_from_slice(key);
+				for i in $key_words..$rounds {
+					let mut tmp: $word = $name::rotate_right(propagated[i-1], 3);
+					if $key_words == 4 {
+						tmp ^= propagated[i-3];
+					}
+					tmp ^= $name::rotate_right(tmp, 1);
+					let z_bit: $word = (($z >> ((i - $key_words) % Z_LEN)) & 1) as $word;
+					propagated[i] = (!propagated[i-$key_words] & $name::MASK) ^ tmp ^ z_bit ^ 3;
+				}
+			}
+
+			pub fn simon_encrypt(&self, mut plaintext1: $word, mut plaintext2: $word) -> ($word, $word) {
+				for curr_key in &self.keys_propagated {
+					let tmp: $word = plaintext2;
+					plaintext2 = plaintext1 ^ $name::f(plaintext2) ^ curr_key;
+					plaintext1 = tmp;
+				}
+
+				(plaintext1, plaintext2)
+			}
+
+			pub fn simon_decrypt(&self, mut ciphertext1: $word, mut ciphertext2: $word) -> ($word, $word) {
+				for curr_key in self.keys_propagated.iter().rev() {
+					let tmp: $word = ciphertext1;
+					ciphertext1 = ciphertext2 ^ $name::f(ciphertext1) ^ curr_key;
+					ciphertext2 = tmp;
+				}
+
+				(ciphertext1, ciphertext2)
+			}
+
+			#[inline]
+			fn f(x: $word) -> $word {
+				($name::rotate_left(x, 1) & $name::rotate_left(x, 8)) ^ $name::rotate_left(x, 2)
+			}
+
+			#[inline]
+			fn rotate_right(x: $word, r: u32) -> $word {
+				((x >> r) | (x << ($bits - r))) & $name::MASK
+			}
+
+			#[inline]
+			fn rotate_left(x: $word, r: u32) -> $word {
+				((x << r) | (x >> ($bits - r))) & $name::MASK
+			}
+		}
+
+		impl BlockCipher for $name {
+			const BLOCK_SIZE: usize = 2 * $name::WORD_BYTES;
+			const KEY_SIZE: usize = $key_words * $name::WORD_BYTES;
+
+			fn from_key_bytes(key: &[u8]) -> $name {
+				$name::from_key_bytes(key)
+			}
+
+			fn encrypt_block(&self, block: &mut [u8]) {
+				assert!(block.len() == $name::BLOCK_SIZE, "Block must have {0} bytes!", $name::BLOCK_SIZE);
+				let (word1, word2) = block.split_at_mut($name::WORD_BYTES);
+				let (a, b) = self.simon_encrypt($name::load_word(word1), $name::load_word(word2));
+				$name::store_word(a, word1);
+				$name::store_word(b, word2);
+			}
+
+			fn decrypt_block(&self, block: &mut [u8]) {
+				assert!(block.len() == $name::BLOCK_SIZE, "Block must have {0} bytes!", $name::BLOCK_SIZE);
+				let (word1, word2) = block.split_at_mut($name::WORD_BYTES);
+				let (a, b) = self.simon_decrypt($name::load_word(word1), $name::load_word(word2));
+				$name::store_word(a, word1);
+				$name::store_word(b, word2);
+			}
+		}
+	}
+}
+
+simon_impl!(Simon_32_64,   u16, 16, 4, 32, Z0);
+simon_impl!(Simon_48_72,   u32, 24, 3, 36, Z0);
+simon_impl!(Simon_48_96,   u32, 24, 4, 36, Z1);
+simon_impl!(Simon_64_96,   u32, 32, 3, 42, Z2);
+simon_impl!(Simon_64_128,  u32, 32, 4, 44, Z3);
+simon_impl!(Simon_96_96,   u64, 48, 2, 52, Z2);
+simon_impl!(Simon_96_144,  u64, 48, 3, 54, Z3);
+simon_impl!(Simon_128_128, u64, 64, 2, 68, Z2);
+simon_impl!(Simon_128_192, u64, 64, 3, 69, Z3);
+simon_impl!(Simon_128_256, u64, 64, 4, 72, Z4);
+
+// Test vectors from "The SIMON and SPECK Families of Lightweight Block Ciphers", Appendix B.
+// The paper prints words from the last to the first one, so here they are reversed.
+#[test]
+fn simon_32_64_works() {
+	let key: [u16; 4] = [0x0100, 0x0908, 0x1110, 0x1918];
+	let plain_text: [u16; 2] = [0x6877, 0x6565];
+	let expected_ciphertext: [u16; 2] = [0xe9bb, 0xc69b];
+
+	let s: Simon_32_64 = Simon_32_64::new(&key);
+
+	let (cypher_a, cypher_b) = s.simon_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.simon_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn simon_48_72_works() {
+	let key: [u32; 3] = [0x020100, 0x0a0908, 0x121110];
+	let plain_text: [u32; 2] = [0x6e696c, 0x612067];
+	let expected_ciphertext: [u32; 2] = [0x292cac, 0xdae5ac];
+
+	let s: Simon_48_72 = Simon_48_72::new(&key);
+
+	let (cypher_a, cypher_b) = s.simon_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.simon_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn simon_48_96_works() {
+	let key: [u32; 4] = [0x020100, 0x0a0908, 0x121110, 0x1a1918];
+	let plain_text: [u32; 2] = [0x20646e, 0x726963];
+	let expected_ciphertext: [u32; 2] = [0xacf156, 0x6e06a5];
+
+	let s: Simon_48_96 = Simon_48_96::new(&key);
+
+	let (cypher_a, cypher_b) = s.simon_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.simon_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn simon_64_96_works() {
+	let key: [u32; 3] = [0x03020100, 0x0b0a0908, 0x13121110];
+	let plain_text: [u32; 2] = [0x6e696c63, 0x6f722067];
+	let expected_ciphertext: [u32; 2] = [0x111a8fc8, 0x5ca2e27f];
+
+	let s: Simon_64_96 = Simon_64_96::new(&key);
+
+	let (cypher_a, cypher_b) = s.simon_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.simon_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn simon_64_128_works() {
+	let key: [u32; 4] = [0x03020100, 0x0b0a0908, 0x13121110, 0x1b1a1918];
+	let plain_text: [u32; 2] = [0x20646e75, 0x656b696c];
+	let expected_ciphertext: [u32; 2] = [0xb9dfa07a, 0x44c8fc20];
+
+	let s: Simon_64_128 = Simon_64_128::new(&key);
+
+	let (cypher_a, cypher_b) = s.simon_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.simon_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn simon_96_96_works() {
+	let key: [u64; 2] = [0x050403020100, 0x0d0c0b0a0908];
+	let plain_text: [u64; 2] = [0x702065687420, 0x2072616c6c69];
+	let expected_ciphertext: [u64; 2] = [0x69063d8ff082, 0x602807a462b4];
+
+	let s: Simon_96_96 = Simon_96_96::new(&key);
+
+	let (cypher_a, cypher_b) = s.simon_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.simon_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn simon_96_144_works() {
+	let key: [u64; 3] = [0x050403020100, 0x0d0c0b0a0908, 0x151413121110];
+	let plain_text: [u64; 2] = [0x73756420666f, 0x746168742074];
+	let expected_ciphertext: [u64; 2] = [0x3f59c5db1ae9, 0xecad1c6c451e];
+
+	let s: Simon_96_144 = Simon_96_144::new(&key);
+
+	let (cypher_a, cypher_b) = s.simon_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.simon_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn simon_128_128_works() {
+	let key: [u64; 2] = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let plain_text: [u64; 2] = [0x6c6c657661727420, 0x6373656420737265];
+	let expected_ciphertext: [u64; 2] = [0x65aa832af84e0bbc, 0x49681b1e1e54fe3f];
+
+	let s: Simon_128_128 = Simon_128_128::new(&key);
+
+	let (cypher_a, cypher_b) = s.simon_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.simon_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn simon_128_192_works() {
+	let key: [u64; 3] = [0x0706050403020100, 0x0f0e0d0c0b0a0908, 0x1716151413121110];
+	let plain_text: [u64; 2] = [0x6568772065626972, 0x206572656874206e];
+	let expected_ciphertext: [u64; 2] = [0x6c9c8d6e2597b85b, 0xc4ac61effcdc0d4f];
+
+	let s: Simon_128_192 = Simon_128_192::new(&key);
+
+	let (cypher_a, cypher_b) = s.simon_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.simon_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn simon_128_256_works() {
+	let key: [u64; 4] = [0x0706050403020100, 0x0f0e0d0c0b0a0908, 0x1716151413121110, 0x1f1e1d1c1b1a1918];
+	let plain_text: [u64; 2] = [0x6d69732061207369, 0x74206e69206d6f6f];
+	let expected_ciphertext: [u64; 2] = [0x3bf72a87efe7b868, 0x8d2b5579afc8a3a0];
+
+	let s: Simon_128_256 = Simon_128_256::new(&key);
+
+	let (cypher_a, cypher_b) = s.simon_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.simon_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn simon_key_bytes_works() {
+	let key: Vec<u8> = (0..32).collect();
+
+	let s1: Simon_128_128 = Simon_128_128::from_key_bytes(&key[..16]);
+	assert_eq!(s1.simon_encrypt(0x6c6c657661727420, 0x6373656420737265), (0x65aa832af84e0bbc, 0x49681b1e1e54fe3f));
+
+	let s2: Simon_128_256 = Simon_128_256::from_key_bytes(&key);
+	assert_eq!(s2.simon_encrypt(0x6d69732061207369, 0x74206e69206d6f6f), (0x3bf72a87efe7b868, 0x8d2b5579afc8a3a0));
+}
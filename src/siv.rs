@@ -0,0 +1,227 @@
+#![allow(unused_parens)]
+
+// Synthetic IV mode (RFC 5297) for 128-bit block ciphers: deterministic and nonce-misuse resistant.
+// S2V over CMAC turns all associated data components and the plaintext into V,
+// V is the tag and (with two bits cleared) the initial counter of CTR.
+// A nonce, if any, is passed as the last associated data component.
+// Output is V followed by the ciphertext.
+
+use block128::Block128;
+use block_cipher::BlockCipher;
+use speck_128_128::Speck_128_128;
+use mac::cmac::{OmacState, Subkeys};
+use util;
+
+const BLOCK_LEN: usize = 16;
+// S2V takes at most 127 components, the last one is the plaintext
+pub const MAX_AD_COMPONENTS: usize = 126;
+
+#[derive(Debug, PartialEq)]
+pub enum SivError {
+	TooManyComponents,
+	WrongInput,
+	AuthenticationFailed,
+}
+
+pub struct SIV <BC = Speck_128_128> {
+	mac_cipher: BC,
+	ctr_cipher: BC,
+	subkeys: Subkeys,
+}
+
+impl SIV {
+	pub fn new<U: Into<Block128>>(mac_key: U, ctr_key: U) -> SIV {
+		SIV::with_ciphers(Speck_128_128::new(mac_key.into()), Speck_128_128::new(ctr_key.into()))
+	}
+}
+
+impl <BC: BlockCipher> SIV <BC> {
+	pub fn with_ciphers(mac_cipher: BC, ctr_cipher: BC) -> SIV<BC> {
+		assert!(BC::BLOCK_SIZE == BLOCK_LEN, "SIV is defined for 128-bit blocks only!");
+		let subkeys: Subkeys = Subkeys::new(&mac_cipher);
+		SIV {mac_cipher, ctr_cipher, subkeys}
+	}
+
+	pub fn seal(&self, associated_data: &[&[u8]], plaintext: &[u8]) -> Result<Vec<u8>, SivError> {
+		if (associated_data.len() > MAX_AD_COMPONENTS) { return Err(SivError::TooManyComponents) };
+
+		let v: [u8; BLOCK_LEN] = self.s2v(associated_data, plaintext);
+		let mut ciphertext: Vec<u8> = Vec::with_capacity(BLOCK_LEN + plaintext.len());
+		ciphertext.extend_from_slice(&v);
+		ciphertext.extend_from_slice(plaintext);
+		self.ctr(&v, &mut ciphertext[BLOCK_LEN..]);
+		Ok(ciphertext)
+	}
+
+	pub fn open(&self, associated_data: &[&[u8]], ciphertext: &[u8]) -> Result<Vec<u8>, SivError> {
+		if (associated_data.len() > MAX_AD_COMPONENTS) { return Err(SivError::TooManyComponents) };
+		if (ciphertext.len() < BLOCK_LEN) { return Err(SivError::WrongInput) };
+
+		let (v, encrypted) = ciphertext.split_at(BLOCK_LEN);
+		let mut decrypted: Vec<u8> = encrypted.to_vec();
+		self.ctr(v, &mut decrypted);
+
+		let expected_v: [u8; BLOCK_LEN] = self.s2v(associated_data, &decrypted);
+		if (!util::constant_time_eq(&expected_v, v)) {
+			util::wipe(&mut decrypted);
+			return Err(SivError::AuthenticationFailed);
+		}
+		Ok(decrypted)
+	}
+
+	// Deterministic key wrapping (RFC 5297 section 1.3.1): no nonce, header is the only component
+	pub fn wrap_key(&self, header: &[u8], key_data: &[u8]) -> Result<Vec<u8>, SivError> {
+		self.seal(&[header], key_data)
+	}
+
+	pub fn unwrap_key(&self, header: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, SivError> {
+		self.open(&[header], wrapped)
+	}
+
+	fn cmac(&self, data: &[u8]) -> Block128 {
+		let mut omac: OmacState = OmacState::new();
+		omac.update(&self.mac_cipher, data);
+		Block128::from(u128::from_be_bytes(omac.finalize(&self.mac_cipher, &self.subkeys)))
+	}
+
+	fn s2v(&self, associated_data: &[&[u8]], plaintext: &[u8]) -> [u8; BLOCK_LEN] {
+		let mut d: Block128 = self.cmac(&[0; BLOCK_LEN]);
+		for component in associated_data {
+			d = d.double() ^ self.cmac(component);
+		}
+
+		let mut omac: OmacState = OmacState::new();
+		if (plaintext.len() >= BLOCK_LEN) {
+			//xorend: D is added to the last block of the plaintext
+			let (head, tail) = plaintext.split_at(plaintext.len() - BLOCK_LEN);
+			let mut last_block: [u8; BLOCK_LEN] = d.to_bytes();
+			util::xor_in_place(&mut last_block, tail);
+			omac.update(&self.mac_cipher, head);
+			omac.update(&self.mac_cipher, &last_block);
+		} else {
+			let mut last_block: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+			last_block[..plaintext.len()].copy_from_slice(plaintext);
+			last_block[plaintext.len()] = 0x80;
+			util::xor_in_place(&mut last_block, &d.double().to_bytes());
+			omac.update(&self.mac_cipher, &last_block);
+		}
+		omac.finalize(&self.mac_cipher, &self.subkeys)
+	}
+
+	// Bits 63 and 31 of V are cleared, so implementations with 32 or 64-bit counters are compatible
+	fn ctr(&self, v: &[u8], data: &mut [u8]) {
+		let mut initial_counter: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		initial_counter.copy_from_slice(v);
+		let mut counter: u128 = u128::from_be_bytes(initial_counter) & !((1 << 63) | (1 << 31));
+		for chunk in data.chunks_mut(BLOCK_LEN) {
+			let mut keystream: [u8; BLOCK_LEN] = counter.to_be_bytes();
+			self.ctr_cipher.encrypt_block(&mut keystream);
+			util::xor_in_place(chunk, &keystream);
+			counter = counter.wrapping_add(1);
+		}
+	}
+}
+
+#[test]
+fn siv_works1() {
+	//Keys and data of RFC 5297 A.1, deterministic key wrapping
+	let mac_key: [u64; 2] = [0xf8f9fafbfcfdfeff, 0xf0f1f2f3f4f5f6f7];
+	let ctr_key: [u64; 2] = [0xf7f6f5f4f3f2f1f0, 0xfffefdfcfbfaf9f8];
+	let header: Vec<u8> = (0x10..0x28).collect();
+	let key_data: [u8; 14] = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee];
+	let expected: [u8; 30] = [
+		0x0b, 0xd6, 0x64, 0x1d, 0x92, 0x26, 0x39, 0x7b, 0xf3, 0xe9, 0xeb, 0x4b, 0x27, 0x7f, 0x3d, 0x73,
+		0x7c, 0x6b, 0x55, 0x8b, 0x1e, 0x7e, 0xd2, 0x84, 0x3d, 0xb2, 0xff, 0x0b, 0xcd, 0xff];
+
+	let c: SIV = SIV::new(&mac_key, &ctr_key);
+	let wrapped: Vec<u8> = c.wrap_key(&header, &key_data).unwrap();
+	assert_eq!(wrapped.as_slice(), &expected[..]);
+	assert_eq!(c.unwrap_key(&header, &wrapped).unwrap().as_slice(), &key_data[..]);
+
+	//Deterministic: the same input gives the same output
+	assert_eq!(c.seal(&[&header], &key_data).unwrap(), wrapped);
+}
+
+#[test]
+fn siv_works2() {
+	//Keys and data of RFC 5297 A.2: two associated data components and a nonce
+	let mac_key: [u64; 2] = [0x78797a7b7c7d7e7f, 0x7071727374757677];
+	let ctr_key: [u64; 2] = [0x4746454443424140, 0x4f4e4d4c4b4a4948];
+	let ad1: [u8; 40] = [
+		0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff,
+		0xde, 0xad, 0xda, 0xda, 0xde, 0xad, 0xda, 0xda, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88,
+		0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00];
+	let ad2: [u8; 10] = [0x10, 0x20, 0x30, 0x40, 0x50, 0x60, 0x70, 0x80, 0x90, 0xa0];
+	let nonce: [u8; 16] = [0x09, 0xf9, 0x11, 0x02, 0x9d, 0x74, 0xe3, 0x5b, 0xd8, 0x41, 0x56, 0xc5, 0x63, 0x56, 0x88, 0xc0];
+	let plaintext: &[u8] = b"this is some plaintext to encrypt using SIV-AES";
+	let expected: [u8; 63] = [
+		0xb3, 0x4e, 0x18, 0xaf, 0xc6, 0xde, 0x65, 0x81, 0x5a, 0xab, 0xb4, 0x4b, 0x05, 0x3d, 0x07, 0x54,
+		0x04, 0x00, 0x60, 0x0e, 0xc3, 0x10, 0xc1, 0xd7, 0x97, 0xb9, 0x0b, 0x8b, 0x6d, 0xb1, 0x0f, 0x46,
+		0x72, 0xb3, 0x56, 0x64, 0xf6, 0x72, 0xa3, 0x7d, 0x49, 0x78, 0x8c, 0xfc, 0xbb, 0x77, 0xc4, 0x20,
+		0x12, 0xac, 0xc6, 0x24, 0x42, 0x90, 0xb0, 0xcb, 0xe6, 0x83, 0xa8, 0xb5, 0xb2, 0xfd, 0x7e];
+
+	let c: SIV = SIV::new(&mac_key, &ctr_key);
+	let ciphertext: Vec<u8> = c.seal(&[&ad1, &ad2, &nonce], plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.open(&[&ad1, &ad2, &nonce], &ciphertext).unwrap().as_slice(), plaintext);
+
+	//Order of components matters
+	assert_eq!(c.open(&[&ad2, &ad1, &nonce], &ciphertext), Err(SivError::AuthenticationFailed));
+	assert_eq!(c.open(&[&ad1, &ad2], &ciphertext), Err(SivError::AuthenticationFailed));
+}
+
+#[test]
+fn siv_works3() {
+	//Empty plaintext without associated data: V only
+	let mac_key: [u64; 2] = [0xf8f9fafbfcfdfeff, 0xf0f1f2f3f4f5f6f7];
+	let ctr_key: [u64; 2] = [0xf7f6f5f4f3f2f1f0, 0xfffefdfcfbfaf9f8];
+	let expected: [u8; 16] = [0x7e, 0x84, 0xd0, 0xb1, 0x8c, 0x31, 0x39, 0xed, 0x92, 0x47, 0xc7, 0xbb, 0x1c, 0x82, 0xcc, 0x7c];
+
+	let c: SIV = SIV::new(&mac_key, &ctr_key);
+	let ciphertext: Vec<u8> = c.seal(&[], &[]).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(c.open(&[], &ciphertext).unwrap(), vec![]);
+}
+
+#[test]
+fn siv_errors() {
+	let mac_key: [u64; 2] = [0xf8f9fafbfcfdfeff, 0xf0f1f2f3f4f5f6f7];
+	let ctr_key: [u64; 2] = [0xf7f6f5f4f3f2f1f0, 0xfffefdfcfbfaf9f8];
+	let plaintext: Vec<u8> = (0..33).collect();
+
+	let c: SIV = SIV::new(&mac_key, &ctr_key);
+	let ciphertext: Vec<u8> = c.seal(&[b"header"], &plaintext).unwrap();
+	util::assert_bit_flips_detected(&ciphertext, |x| c.open(&[b"header"], x), SivError::AuthenticationFailed);
+	assert_eq!(c.open(&[b"header"], &ciphertext[..15]), Err(SivError::WrongInput));
+
+	let components: Vec<&[u8]> = vec![b"header"; MAX_AD_COMPONENTS + 1];
+	assert_eq!(c.seal(&components, &plaintext), Err(SivError::TooManyComponents));
+	assert!(c.seal(&components[1..], &plaintext).is_ok());
+}
+
+#[test]
+fn siv_ad_components() {
+	let mac_key: [u64; 2] = [0xf8f9fafbfcfdfeff, 0xf0f1f2f3f4f5f6f7];
+	let ctr_key: [u64; 2] = [0xf7f6f5f4f3f2f1f0, 0xfffefdfcfbfaf9f8];
+	let plaintext: Vec<u8> = (0..33).collect();
+	let c: SIV = SIV::new(&mac_key, &ctr_key);
+
+	//Every component is MACed on its own, so borders between them are authenticated
+	let ciphertext: Vec<u8> = c.seal(&[b"header", b"nonce"], &plaintext).unwrap();
+	assert_eq!(c.open(&[b"head", b"er", b"nonce"], &ciphertext), Err(SivError::AuthenticationFailed));
+	assert_eq!(c.open(&[b"headernonce"], &ciphertext), Err(SivError::AuthenticationFailed));
+
+	//An empty component is not the same as no component
+	let ciphertext: Vec<u8> = c.seal(&[&[]], &plaintext).unwrap();
+	assert_eq!(c.open(&[&[]], &ciphertext).unwrap(), plaintext);
+	assert_eq!(c.open(&[], &ciphertext), Err(SivError::AuthenticationFailed));
+
+	//All the components S2V can take, the last one is still checked
+	let mut components: Vec<&[u8]> = vec![b"header"; MAX_AD_COMPONENTS];
+	let ciphertext: Vec<u8> = c.seal(&components, &plaintext).unwrap();
+	assert_eq!(c.open(&components, &ciphertext).unwrap(), plaintext);
+	components[MAX_AD_COMPONENTS - 1] = b"Header";
+	assert_eq!(c.open(&components, &ciphertext), Err(SivError::AuthenticationFailed));
+	components.push(b"header");
+	assert_eq!(c.open(&components, &ciphertext), Err(SivError::TooManyComponents));
+}
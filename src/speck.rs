@@ -0,0 +1,296 @@
+// The rest of SPECK family. Speck_128_128 lives in its own module.
+// Words are kept in the same order as in speck_128_128: key[0] is k0,
+// key[1..] is l0, l1, ...; the first word of a block is y, the second is x.
+
+use block_cipher::BlockCipher;
+use util;
+
+macro_rules! speck_impl {
+	($name: ident, $word: ty, $bits: expr, $key_words: expr, $rounds: expr, $alpha: expr, $beta: expr) => {
+		#[allow(non_camel_case_types)]
+		pub struct $name {
+			keys_propagated: [$word; $rounds]
+		}
+
+		impl $name {
+			const MASK: $word = (!0 as $word) >> (::std::mem::size_of::<$word>() * 8 - $bits);
+			const WORD_BYTES: usize = $bits / 8;
+
+			pub fn new(key: &[$word; $key_words]) -> $name {
+				let mut key_temp: [$word; $rounds] = [0; $rounds];
+				$name::key_schedule(key, &mut key_temp);
+				$name {keys_propagated: key_temp}
+			}
+
+			//Words are read little-endian, k0 comes first: the same bytes as in speck_128_128
+			pub fn from_key_bytes(key: &[u8]) -> $name {
+				assert!(key.len() == $key_words * $name::WORD_BYTES, "Key must have {0} bytes!", $key_words * $name::WORD_BYTES);
+				let mut key_words: [$word; $key_words] = [0; $key_words];
+				util::load_words_le(key, $name::WORD_BYTES, &mut key_words);
+				$name::new(&key_words)
+			}
+
+			#[inline]
+			fn store_word(word: $word, bytes: &mut [u8]) {
+				for (i, x) in bytes.iter_mut().enumerate() {
+					*x = (word >> (8 * i)) as u8;
+				}
+			}
+
+			fn key_schedule(key: &[$word; $key_words], propagated: &mut [$word; $rounds]) {
+				let mut l: [$word; $key_words - 1] = [0; $key_words - 1];
+				l.copy_from_slice(&key[1..]);
+				let mut k: $word = key[0];
+				propagated[0] = k;
+				for (i, item) in propagated.iter_mut().enumerate().skip(1) {
+					let j: usize = (i - 1) % l.len();
+					let l_curr: &mut $word = &mut l[j];
+					$name::round_forward(l_curr, &mut k, (i - 1) as $word);
+					*item = k;
+				}
+			}
+
+			pub fn speck_encrypt(&self, mut plaintext1: $word, mut plaintext2: $word) -> ($word, $word) {
+				for curr_key in &self.keys_propagated {
+					$name::round_forward(&mut plaintext2, &mut plaintext1, *curr_key);
+				}
+
+				(plaintext1, plaintext2)
+			}
+
+			pub fn speck_decrypt(&self, mut ciphertext1: $word, mut ciphertext2: $word) -> ($word, $word) {
+				for curr_key in self.keys_propagated.iter().rev() {
+					$name::round_backward(&mut ciphertext2, &mut ciphertext1, *curr_key);
+				}
+
+				(ciphertext1, ciphertext2)
+			}
+
+			#[inline]
+			fn rotate_right(x: $word, r: u32) -> $word {
+				((x >> r) | (x << ($bits - r))) & $name::MASK
+			}
+
+			#[inline]
+			fn rotate_left(x: $word, r: u32) -> $word {
+				((x << r) | (x >> ($bits - r))) & $name::MASK
+			}
+
+			#[inline]
+			fn round_forward(x1: &mut $word, x2: &mut $word, key: $word) {
+				*x1 = ($name::rotate_right(*x1, $alpha).wrapping_add(*x2) & $name::MASK) ^ key;
+				*x2 = $name::rotate_left(*x2, $beta) ^ (*x1);
+			}
+
+			#[inline]
+			fn round_backward(x1: &mut $word, x2: &mut $word, key: $word) {
+				*x2 = $name::rotate_right(*x2 ^ *x1, $beta);
+				*x1 = $name::rotate_left((*x1 ^ key).wrapping_sub(*x2) & $name::MASK, $alpha);
+			}
+		}
+
+		impl BlockCipher for $name {
+			const BLOCK_SIZE: usize = 2 * $name::WORD_BYTES;
+			const KEY_SIZE: usize = $key_words * $name::WORD_BYTES;
+
+			fn from_key_bytes(key: &[u8]) -> $name {
+				$name::from_key_bytes(key)
+			}
+
+			fn encrypt_block(&self, block: &mut [u8]) {
+				assert!(block.len() == $name::BLOCK_SIZE, "Block must have {0} bytes!", $name::BLOCK_SIZE);
+				let mut words: [$word; 2] = [0; 2];
+				util::load_words_le(block, $name::WORD_BYTES, &mut words);
+				let (a, b) = self.speck_encrypt(words[0], words[1]);
+				let (word1, word2) = block.split_at_mut($name::WORD_BYTES);
+				$name::store_word(a, word1);
+				$name::store_word(b, word2);
+			}
+
+			fn decrypt_block(&self, block: &mut [u8]) {
+				assert!(block.len() == $name::BLOCK_SIZE, "Block must have {0} bytes!", $name::BLOCK_SIZE);
+				let mut words: [$word; 2] = [0; 2];
+				util::load_words_le(block, $name::WORD_BYTES, &mut words);
+				let (a, b) = self.speck_decrypt(words[0], words[1]);
+				let (word1, word2) = block.split_at_mut($name::WORD_BYTES);
+				$name::store_word(a, word1);
+				$name::store_word(b, word2);
+			}
+		}
+	}
+}
+
+speck_impl!(Speck_32_64,   u16, 16, 4, 22, 7, 2);
+speck_impl!(Speck_48_72,   u32, 24, 3, 22, 8, 3);
+speck_impl!(Speck_48_96,   u32, 24, 4, 23, 8, 3);
+speck_impl!(Speck_64_96,   u32, 32, 3, 26, 8, 3);
+speck_impl!(Speck_64_128,  u32, 32, 4, 27, 8, 3);
+speck_impl!(Speck_96_96,   u64, 48, 2, 28, 8, 3);
+speck_impl!(Speck_96_144,  u64, 48, 3, 29, 8, 3);
+speck_impl!(Speck_128_192, u64, 64, 3, 33, 8, 3);
+speck_impl!(Speck_128_256, u64, 64, 4, 34, 8, 3);
+
+// Test vectors from "The SIMON and SPECK Families of Lightweight Block Ciphers", Appendix C.
+// The paper prints words from the last to the first one, so here they are reversed.
+#[test]
+fn speck_32_64_works() {
+	let key: [u16; 4] = [0x0100, 0x0908, 0x1110, 0x1918];
+	let plain_text: [u16; 2] = [0x694c, 0x6574];
+	let expected_ciphertext: [u16; 2] = [0x42f2, 0xa868];
+
+	let s: Speck_32_64 = Speck_32_64::new(&key);
+
+	let (cypher_a, cypher_b) = s.speck_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.speck_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn speck_48_72_works() {
+	let key: [u32; 3] = [0x020100, 0x0a0908, 0x121110];
+	let plain_text: [u32; 2] = [0x6c6172, 0x20796c];
+	let expected_ciphertext: [u32; 2] = [0x385adc, 0xc049a5];
+
+	let s: Speck_48_72 = Speck_48_72::new(&key);
+
+	let (cypher_a, cypher_b) = s.speck_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.speck_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn speck_48_96_works() {
+	let key: [u32; 4] = [0x020100, 0x0a0908, 0x121110, 0x1a1918];
+	let plain_text: [u32; 2] = [0x696874, 0x6d2073];
+	let expected_ciphertext: [u32; 2] = [0xb6445d, 0x735e10];
+
+	let s: Speck_48_96 = Speck_48_96::new(&key);
+
+	let (cypher_a, cypher_b) = s.speck_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.speck_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn speck_64_96_works() {
+	let key: [u32; 3] = [0x03020100, 0x0b0a0908, 0x13121110];
+	let plain_text: [u32; 2] = [0x736e6165, 0x74614620];
+	let expected_ciphertext: [u32; 2] = [0x4175946c, 0x9f7952ec];
+
+	let s: Speck_64_96 = Speck_64_96::new(&key);
+
+	let (cypher_a, cypher_b) = s.speck_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.speck_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn speck_64_128_works() {
+	let key: [u32; 4] = [0x03020100, 0x0b0a0908, 0x13121110, 0x1b1a1918];
+	let plain_text: [u32; 2] = [0x7475432d, 0x3b726574];
+	let expected_ciphertext: [u32; 2] = [0x454e028b, 0x8c6fa548];
+
+	let s: Speck_64_128 = Speck_64_128::new(&key);
+
+	let (cypher_a, cypher_b) = s.speck_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.speck_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn speck_96_96_works() {
+	let key: [u64; 2] = [0x050403020100, 0x0d0c0b0a0908];
+	let plain_text: [u64; 2] = [0x656761737520, 0x65776f68202c];
+	let expected_ciphertext: [u64; 2] = [0x62bdde8f79aa, 0x9e4d09ab7178];
+
+	let s: Speck_96_96 = Speck_96_96::new(&key);
+
+	let (cypher_a, cypher_b) = s.speck_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.speck_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn speck_96_144_works() {
+	let key: [u64; 3] = [0x050403020100, 0x0d0c0b0a0908, 0x151413121110];
+	let plain_text: [u64; 2] = [0x69202c726576, 0x656d6974206e];
+	let expected_ciphertext: [u64; 2] = [0x7ae440252ee6, 0x2bf31072228a];
+
+	let s: Speck_96_144 = Speck_96_144::new(&key);
+
+	let (cypher_a, cypher_b) = s.speck_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.speck_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn speck_128_192_works() {
+	let key: [u64; 3] = [0x0706050403020100, 0x0f0e0d0c0b0a0908, 0x1716151413121110];
+	let plain_text: [u64; 2] = [0x43206f7420746e65, 0x7261482066656968];
+	let expected_ciphertext: [u64; 2] = [0xf9bc185de03c1886, 0x1be4cf3a13135566];
+
+	let s: Speck_128_192 = Speck_128_192::new(&key);
+
+	let (cypher_a, cypher_b) = s.speck_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.speck_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn speck_128_256_works() {
+	let key: [u64; 4] = [0x0706050403020100, 0x0f0e0d0c0b0a0908, 0x1716151413121110, 0x1f1e1d1c1b1a1918];
+	let plain_text: [u64; 2] = [0x202e72656e6f6f70, 0x65736f6874206e49];
+	let expected_ciphertext: [u64; 2] = [0x4eeeb48d9c188f43, 0x4109010405c0f53e];
+
+	let s: Speck_128_256 = Speck_128_256::new(&key);
+
+	let (cypher_a, cypher_b) = s.speck_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+
+	let (decr_a, decr_b) = s.speck_decrypt(cypher_a, cypher_b);
+	assert_eq!([decr_a, decr_b], plain_text);
+}
+
+#[test]
+fn speck_key_bytes_works() {
+	let key: Vec<u8> = (0..32).collect();
+
+	let s1: Speck_32_64 = Speck_32_64::from_key_bytes(&[0x00, 0x01, 0x08, 0x09, 0x10, 0x11, 0x18, 0x19]);
+	assert_eq!(s1.speck_encrypt(0x694c, 0x6574), (0x42f2, 0xa868));
+
+	let s2: Speck_48_72 = Speck_48_72::from_key_bytes(&[0x00, 0x01, 0x02, 0x08, 0x09, 0x0a, 0x10, 0x11, 0x12]);
+	assert_eq!(s2.speck_encrypt(0x6c6172, 0x20796c), (0x385adc, 0xc049a5));
+
+	let s3: Speck_96_144 = Speck_96_144::from_key_bytes(&[0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+		0x10, 0x11, 0x12, 0x13, 0x14, 0x15]);
+	assert_eq!(s3.speck_encrypt(0x69202c726576, 0x656d6974206e), (0x7ae440252ee6, 0x2bf31072228a));
+
+	let s4: Speck_128_192 = Speck_128_192::from_key_bytes(&key[..24]);
+	assert_eq!(s4.speck_encrypt(0x43206f7420746e65, 0x7261482066656968), (0xf9bc185de03c1886, 0x1be4cf3a13135566));
+
+	let s5: Speck_128_256 = Speck_128_256::from_key_bytes(&key);
+	assert_eq!(s5.speck_encrypt(0x202e72656e6f6f70, 0x65736f6874206e49), (0x4eeeb48d9c188f43, 0x4109010405c0f53e));
+}
+
+#[test]
+#[should_panic]
+fn speck_key_bytes_panic() {
+	//Should fail on 16 bytes
+	let _s: Speck_128_256 = Speck_128_256::from_key_bytes(&[0u8; 16]);
+}
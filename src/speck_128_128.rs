@@ -1,8 +1,11 @@
 use block128::Block128;
+use block_cipher::BlockCipher;
+use util;
 
 const ALPHA:  u32   = 8;
 const BETA:   u32   = 3;
 const ROUNDS: usize = 32;
+const KEY_WORDS: usize = 2;
 
 #[allow(non_camel_case_types)]
 pub struct Speck_128_128 {
@@ -11,18 +14,30 @@ pub struct Speck_128_128 {
 
 impl Speck_128_128 {
 	pub fn new<U: Into<Block128>>(key: U) -> Speck_128_128 {
+		let key_block: Block128 = key.into();
 		let mut key_temp: [u64; ROUNDS] = [0; ROUNDS];
-		Speck_128_128::key_schedule(&key.into(), &mut key_temp);
+		Speck_128_128::key_schedule(&[key_block.get_a(), key_block.get_b()], &mut key_temp);
 		Speck_128_128 {keys_propagated: key_temp}
 	}
 
-	fn key_schedule(key: &Block128, propagated: &mut [u64; ROUNDS]) {
-		let mut y1: u64 = key.get_b();
-		let mut y2: u64 = key.get_a();
-		propagated[0] = key.get_a();
-		for (i, item) in &mut propagated.into_iter().enumerate().skip(1) {
-			speck_round_forward(&mut y1, &mut y2, &((i-1) as u64));
-			(*item) = y2;
+	//Words are read little-endian: bytes 00 01 .. 0f give k0 = 0x0706050403020100, l0 = 0x0f0e0d0c0b0a0908
+	pub fn from_key_bytes(key: &[u8]) -> Speck_128_128 {
+		assert!(key.len() == KEY_WORDS * 8, "Key must have {0} bytes!", KEY_WORDS * 8);
+		let mut key_words: [u64; KEY_WORDS] = [0; KEY_WORDS];
+		util::bytes_to_words_le(key, &mut key_words);
+		let mut key_temp: [u64; ROUNDS] = [0; ROUNDS];
+		Speck_128_128::key_schedule(&key_words, &mut key_temp);
+		Speck_128_128 {keys_propagated: key_temp}
+	}
+
+	//key[0] is k0, key[1] is l0
+	fn key_schedule(key: &[u64; KEY_WORDS], propagated: &mut [u64; ROUNDS]) {
+		let mut l: u64 = key[1];
+		let mut k: u64 = key[0];
+		propagated[0] = k;
+		for (i, item) in propagated.iter_mut().enumerate().skip(1) {
+			speck_round_forward(&mut l, &mut k, &((i-1) as u64));
+			(*item) = k;
 		}
 	}
 
@@ -45,6 +60,30 @@ impl Speck_128_128 {
 	}
 }
 
+//Words are little-endian and y comes first, as in the reference implementation guide and Linux kernel
+impl BlockCipher for Speck_128_128 {
+	const BLOCK_SIZE: usize = 16;
+	const KEY_SIZE: usize = KEY_WORDS * 8;
+
+	fn from_key_bytes(key: &[u8]) -> Speck_128_128 {
+		Speck_128_128::from_key_bytes(key)
+	}
+
+	fn encrypt_block(&self, block: &mut [u8]) {
+		let mut words: [u64; 2] = [0; 2];
+		util::bytes_to_words_le(block, &mut words);
+		let (a, b) = self.speck_encrypt(words[0], words[1]);
+		util::words_to_bytes_le(&[a, b], block);
+	}
+
+	fn decrypt_block(&self, block: &mut [u8]) {
+		let mut words: [u64; 2] = [0; 2];
+		util::bytes_to_words_le(block, &mut words);
+		let (a, b) = self.speck_decrypt(words[0], words[1]);
+		util::words_to_bytes_le(&[a, b], block);
+	}
+}
+
 #[inline]
 fn speck_round_forward(x1: &mut u64, x2: &mut u64, key: &u64) {
 	*x1 = ((x1.rotate_right(ALPHA)).wrapping_add(*x2)) ^ key;
@@ -71,3 +110,39 @@ fn basic_works1() {
 	let (decr_a, decr_b) = s.speck_decrypt(cypher_a, cypher_b);
 	assert_eq!([decr_a, decr_b], plain_text);
 }
+
+#[test]
+fn key_bytes_works1() {
+	let key: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f];
+	let plain_text: [u64; 2] = [0x7469206564616d20, 0x6c61766975716520];
+	let expected_ciphertext = [0x7860fedf5c570d18, 0xa65d985179783265];
+
+	let s: Speck_128_128 = Speck_128_128::from_key_bytes(&key);
+
+	let (cypher_a, cypher_b) = s.speck_encrypt(plain_text[0], plain_text[1]);
+	assert_eq!([cypher_a, cypher_b], expected_ciphertext);
+}
+
+#[test]
+#[should_panic]
+fn key_bytes_panic() {
+	//Should fail on 24 bytes, Speck_128_192 must be used for it
+	let key: [u8; 24] = [0; 24];
+	let _s: Speck_128_128 = Speck_128_128::from_key_bytes(&key);
+}
+
+#[test]
+fn block_cipher_works1() {
+	let key: [u8; 16] = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f];
+	let plain_text: [u8; 16] = [0x20, 0x6d, 0x61, 0x64, 0x65, 0x20, 0x69, 0x74, 0x20, 0x65, 0x71, 0x75, 0x69, 0x76, 0x61, 0x6c];
+	let expected_ciphertext: [u8; 16] = [0x18, 0x0d, 0x57, 0x5c, 0xdf, 0xfe, 0x60, 0x78, 0x65, 0x32, 0x78, 0x79, 0x51, 0x98, 0x5d, 0xa6];
+
+	let s: Speck_128_128 = Speck_128_128::from_key_bytes(&key);
+	let mut block: [u8; 16] = plain_text;
+
+	s.encrypt_block(&mut block);
+	assert_eq!(block, expected_ciphertext);
+
+	s.decrypt_block(&mut block);
+	assert_eq!(block, plain_text);
+}
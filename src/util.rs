@@ -1,3 +1,5 @@
+use std::ops::{BitOr, Shl};
+
 pub fn bytes_to_words(input: &[u8]/*, word_len: usize*/) -> &[u64] {
 	//It can be zero-length slice.
 	//Don't forget about endianess!
@@ -12,3 +14,59 @@ pub fn words_to_bytes(input: &[u64]) -> &[u8] {
 fn word_to_bytes(input: u64) -> [u8; 8] {
 	unsafe{::std::mem::transmute(input)}
 }*/
+
+pub fn bytes_to_words_le(input: &[u8], output: &mut [u64]) {
+	load_words_le(input, 8, output);
+}
+
+// Words may be narrower than W: 24-bit Speck words are kept in u32, 48-bit ones in u64
+pub fn load_words_le<W>(input: &[u8], word_bytes: usize, output: &mut [W])
+	where W: From<u8> + Shl<u32, Output = W> + BitOr<Output = W> {
+	assert!(input.len() == output.len() * word_bytes, "Input must have {0} bytes!", output.len() * word_bytes);
+	for (word, chunk) in output.iter_mut().zip(input.chunks(word_bytes)) {
+		*word = chunk.iter().rev().fold(W::from(0), |acc, &x| (acc << 8) | W::from(x));
+	}
+}
+
+pub fn words_to_bytes_le(input: &[u64], output: &mut [u8]) {
+	assert!(output.len() == input.len() * 8, "Output must have {0} bytes!", input.len() * 8);
+	for (&word, chunk) in input.iter().zip(output.chunks_mut(8)) {
+		for (i, x) in chunk.iter_mut().enumerate() {
+			*x = (word >> (8 * i)) as u8;
+		}
+	}
+}
+
+pub fn xor_in_place(output: &mut [u8], input: &[u8]) {
+	for (x, y) in output.iter_mut().zip(input) {
+		*x ^= *y;
+	}
+}
+
+// Time doesn't depend on the position of the first difference, so it can be used for tags
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	if a.len() != b.len() {
+		return false;
+	}
+	a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// Zeroes data that must not stay in memory, such as plaintext that failed authentication.
+// Writes are volatile, so they are not dropped as dead stores when the buffer is freed right after.
+pub fn wipe(data: &mut [u8]) {
+	for x in data.iter_mut() {
+		unsafe { ::std::ptr::write_volatile(x, 0) };
+	}
+	::std::sync::atomic::compiler_fence(::std::sync::atomic::Ordering::SeqCst);
+}
+
+// The tag covers every bit of a sealed message, so flipping any single one has to be detected
+#[cfg(test)]
+pub fn assert_bit_flips_detected<E, F>(sealed: &[u8], open: F, expected: E)
+	where E: PartialEq + ::std::fmt::Debug, F: Fn(&[u8]) -> Result<Vec<u8>, E> {
+	for bit in 0 .. sealed.len() * 8 {
+		let mut damaged: Vec<u8> = sealed.to_vec();
+		damaged[bit / 8] ^= 1 << (bit % 8);
+		assert_eq!(open(&damaged).err().as_ref(), Some(&expected), "Flipped bit {0} was not detected!", bit);
+	}
+}
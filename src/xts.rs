@@ -0,0 +1,325 @@
+#![allow(unused_parens)]
+
+// XTS (IEEE P1619) for sector-level encryption, as Linux does with xts(speck128).
+// Two ciphers: one for data, one for tweak. Tweak of a data unit is its sector number,
+// little-endian and padded with zeros to a block ("plain64" in dm-crypt).
+// A partial final block is handled with ciphertext stealing, so data units don't have to be block-aligned.
+
+use block128::Block128;
+use block_cipher::{BlockCipher, CipherErrors};
+use speck_128_128::Speck_128_128;
+use util;
+#[cfg(test)]
+use speck::Speck_128_256;
+
+const BLOCK_LEN: usize = 16;
+
+pub struct XTS <BC = Speck_128_128> {
+	data_cipher: BC,
+	tweak_cipher: BC,
+	data_unit_len: usize,
+}
+
+impl XTS {
+	pub fn new<U: Into<Block128>>(data_key: U, tweak_key: U, data_unit_len: usize) -> XTS {
+		XTS::with_ciphers(Speck_128_128::new(data_key.into()), Speck_128_128::new(tweak_key.into()), data_unit_len)
+	}
+}
+
+impl <BC: BlockCipher> XTS <BC> {
+	pub fn with_ciphers(data_cipher: BC, tweak_cipher: BC, data_unit_len: usize) -> XTS<BC> {
+		assert!(BC::BLOCK_SIZE == BLOCK_LEN, "XTS is defined for 128-bit blocks only!");
+		assert!(data_unit_len >= BLOCK_LEN, "Data unit must have at least {0} bytes!", BLOCK_LEN);
+		XTS {data_cipher, tweak_cipher, data_unit_len}
+	}
+
+	// Data is split into data units, the first one has number first_sector, next ones are numbered sequentially.
+	// The last unit may be shorter, but it can't be shorter than a block.
+	pub fn xts_encrypt_byte_array(&self, first_sector: u64, plaintext: &[u8]) -> Result<Vec<u8>, CipherErrors> {
+		let mut ciphertext: Vec<u8> = plaintext.to_vec();
+		for (i, data_unit) in ciphertext.chunks_mut(self.data_unit_len).enumerate() {
+			self.encrypt_sector(first_sector.wrapping_add(i as u64), data_unit)?;
+		}
+		Ok(ciphertext)
+	}
+
+	pub fn xts_decrypt_byte_array(&self, first_sector: u64, ciphertext: &[u8]) -> Result<Vec<u8>, CipherErrors> {
+		let mut decrypted: Vec<u8> = ciphertext.to_vec();
+		for (i, data_unit) in decrypted.chunks_mut(self.data_unit_len).enumerate() {
+			self.decrypt_sector(first_sector.wrapping_add(i as u64), data_unit)?;
+		}
+		Ok(decrypted)
+	}
+
+	pub fn encrypt_sector(&self, sector: u64, data: &mut [u8]) -> Result<(), CipherErrors> {
+		if (data.len() < BLOCK_LEN || data.len() > self.data_unit_len) { return Err(CipherErrors::WrongInput) };
+
+		let mut tweak: [u8; BLOCK_LEN] = self.initial_tweak(sector);
+		let last_len: usize = data.len() % BLOCK_LEN;
+		let full_len: usize = data.len() - last_len;
+
+		for block in data[..full_len].chunks_mut(BLOCK_LEN) {
+			self.process_block(block, &tweak, true);
+			mul_by_x(&mut tweak);
+		}
+
+		if (last_len != 0) {
+			//Swap the partial block with the head of the previous one, and encrypt the previous block again
+			let (head, tail) = data.split_at_mut(full_len);
+			let previous: &mut [u8] = &mut head[full_len - BLOCK_LEN ..];
+			previous[..last_len].swap_with_slice(tail);
+			self.process_block(previous, &tweak, true);
+		}
+
+		Ok(())
+	}
+
+	pub fn decrypt_sector(&self, sector: u64, data: &mut [u8]) -> Result<(), CipherErrors> {
+		if (data.len() < BLOCK_LEN || data.len() > self.data_unit_len) { return Err(CipherErrors::WrongInput) };
+
+		let mut tweak: [u8; BLOCK_LEN] = self.initial_tweak(sector);
+		let last_len: usize = data.len() % BLOCK_LEN;
+		let full_len: usize = data.len() - last_len;
+		//With ciphertext stealing the last full block is decrypted with the tweak that comes after it
+		let stealing_len: usize = if (last_len != 0) { BLOCK_LEN } else { 0 };
+
+		for block in data[.. full_len - stealing_len].chunks_mut(BLOCK_LEN) {
+			self.process_block(block, &tweak, false);
+			mul_by_x(&mut tweak);
+		}
+
+		if (last_len != 0) {
+			let mut next_tweak: [u8; BLOCK_LEN] = tweak;
+			mul_by_x(&mut next_tweak);
+
+			let (head, tail) = data.split_at_mut(full_len);
+			let previous: &mut [u8] = &mut head[full_len - BLOCK_LEN ..];
+			self.process_block(previous, &next_tweak, false);
+			previous[..last_len].swap_with_slice(tail);
+			self.process_block(previous, &tweak, false);
+		}
+
+		Ok(())
+	}
+
+	fn initial_tweak(&self, sector: u64) -> [u8; BLOCK_LEN] {
+		let mut tweak: [u8; BLOCK_LEN] = [0; BLOCK_LEN];
+		util::words_to_bytes_le(&[sector], &mut tweak[..8]);
+		self.tweak_cipher.encrypt_block(&mut tweak);
+		tweak
+	}
+
+	#[inline]
+	fn process_block(&self, block: &mut [u8], tweak: &[u8], encrypt: bool) {
+		util::xor_in_place(block, tweak);
+		match encrypt {
+			true  => self.data_cipher.encrypt_block(block),
+			false => self.data_cipher.decrypt_block(block),
+		}
+		util::xor_in_place(block, tweak);
+	}
+}
+
+// Multiplication by x in GF(2^128) with x^128 + x^7 + x^2 + x + 1, byte 0 is the least significant one
+#[inline]
+fn mul_by_x(tweak: &mut [u8; BLOCK_LEN]) {
+	let mut carry: u8 = 0;
+	for x in tweak.iter_mut() {
+		let next_carry: u8 = *x >> 7;
+		*x = (*x << 1) | carry;
+		carry = next_carry;
+	}
+	if (carry != 0) {
+		tweak[0] ^= 0x87;
+	}
+}
+
+// Test vectors of xts(speck128) from speck128_xts_tv_template in Linux crypto/testmgr.h:
+// the IEEE P1619 XTS-AES vectors with the ciphertext recomputed with Speck128
+#[test]
+fn xts_works1() {
+	let plaintext: [u8; 32] = [0; 32];
+	let expected: [u8; 32] = [
+		0xbe, 0xa0, 0xe7, 0x03, 0xd7, 0xfe, 0xab, 0x62, 0x3b, 0x99, 0x4a, 0x64, 0x74, 0x77, 0xac, 0xed,
+		0xd8, 0xf4, 0xa6, 0xcf, 0xae, 0xb9, 0x07, 0x42, 0x51, 0xd9, 0xb6, 0x1d, 0xe0, 0x5e, 0xbc, 0x54];
+
+	let key: [u64; 2] = [0, 0];
+
+	let x: XTS = XTS::new(&key, &key, 512);
+	let ciphertext: Vec<u8> = x.xts_encrypt_byte_array(0, &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(x.xts_decrypt_byte_array(0, &ciphertext).unwrap().as_slice(), &plaintext[..]);
+}
+
+#[test]
+fn xts_works2() {
+	let data_key: [u8; 16]  = [0x11; 16];
+	let tweak_key: [u8; 16] = [0x22; 16];
+	let plaintext: [u8; 32] = [0x44; 32];
+	let expected: [u8; 32] = [
+		0xfb, 0x53, 0x81, 0x75, 0x6f, 0x9f, 0x34, 0xad, 0x7e, 0x01, 0xed, 0x7b, 0xcc, 0xda, 0x4e, 0x4a,
+		0xd4, 0x84, 0xa4, 0x53, 0xd5, 0x88, 0x73, 0x1b, 0xfd, 0xcb, 0xae, 0x0d, 0xf3, 0x04, 0xee, 0xe6];
+
+	let x: XTS = XTS::with_ciphers(Speck_128_128::from_key_bytes(&data_key), Speck_128_128::from_key_bytes(&tweak_key), 512);
+	let ciphertext: Vec<u8> = x.xts_encrypt_byte_array(0x3333333333, &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(x.xts_decrypt_byte_array(0x3333333333, &ciphertext).unwrap().as_slice(), &plaintext[..]);
+}
+
+#[test]
+fn xts_works3() {
+	let data_key: [u8; 16]  = [0xff, 0xfe, 0xfd, 0xfc, 0xfb, 0xfa, 0xf9, 0xf8, 0xf7, 0xf6, 0xf5, 0xf4, 0xf3, 0xf2, 0xf1, 0xf0];
+	let tweak_key: [u8; 16] = [0x22; 16];
+	let plaintext: [u8; 32] = [0x44; 32];
+	let expected: [u8; 32] = [
+		0x21, 0x52, 0x84, 0x15, 0xd1, 0xf7, 0x21, 0x55, 0xd9, 0x75, 0x4a, 0xd3, 0xc5, 0xdb, 0x9f, 0x7d,
+		0xda, 0x63, 0xb2, 0xf1, 0x82, 0xb0, 0x89, 0x59, 0x86, 0xd4, 0xaa, 0xaa, 0xdd, 0xff, 0x4f, 0x92];
+
+	let x: XTS = XTS::with_ciphers(Speck_128_128::from_key_bytes(&data_key), Speck_128_128::from_key_bytes(&tweak_key), 512);
+	let ciphertext: Vec<u8> = x.xts_encrypt_byte_array(0x3333333333, &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(x.xts_decrypt_byte_array(0x3333333333, &ciphertext).unwrap().as_slice(), &plaintext[..]);
+}
+
+#[test]
+fn xts_works4() {
+	let data_key: [u8; 16]  = [0x27, 0x18, 0x28, 0x18, 0x28, 0x45, 0x90, 0x45, 0x23, 0x53, 0x60, 0x28, 0x74, 0x71, 0x35, 0x26];
+	let tweak_key: [u8; 16] = [0x31, 0x41, 0x59, 0x26, 0x53, 0x58, 0x97, 0x93, 0x23, 0x84, 0x62, 0x64, 0x33, 0x83, 0x27, 0x95];
+	let plaintext: Vec<u8> = (0 .. 512).map(|x| x as u8).collect();
+	let expected: [u8; 512] = [
+		0x57, 0xb5, 0xf8, 0x71, 0x6e, 0x6d, 0xdd, 0x82, 0x53, 0xd0, 0xed, 0x2d, 0x30, 0xc1, 0x20, 0xef,
+		0x70, 0x67, 0x5e, 0xff, 0x09, 0x70, 0xbb, 0xc1, 0x3a, 0x7b, 0x48, 0x26, 0xd9, 0x0b, 0xf4, 0x48,
+		0xbe, 0xce, 0xb1, 0xc7, 0xb2, 0x67, 0xc4, 0xa7, 0x76, 0xf8, 0x36, 0x30, 0xb7, 0xb4, 0x9a, 0xd9,
+		0xf5, 0x9d, 0xd0, 0x7b, 0xc1, 0x06, 0x96, 0x44, 0x19, 0xc5, 0x58, 0x84, 0x63, 0xb9, 0x12, 0x68,
+		0x68, 0xc7, 0xaa, 0x18, 0x98, 0xf2, 0x1f, 0x5c, 0x39, 0xa6, 0xd8, 0x32, 0x2b, 0xc3, 0x51, 0xfd,
+		0x74, 0x79, 0x2e, 0xb4, 0x44, 0xd7, 0x69, 0xc4, 0xfc, 0x29, 0xe6, 0xed, 0x26, 0x1e, 0xa6, 0x9d,
+		0x1c, 0xbe, 0x00, 0x0e, 0x7f, 0x3a, 0xca, 0xfb, 0x6d, 0x13, 0x65, 0xa0, 0xf9, 0x31, 0x12, 0xe2,
+		0x26, 0xd1, 0xec, 0x2b, 0x0a, 0x8b, 0x59, 0x99, 0xa7, 0x49, 0xa0, 0x0e, 0x09, 0x33, 0x85, 0x50,
+		0xc3, 0x23, 0xca, 0x7a, 0xdd, 0x13, 0x45, 0x5f, 0xde, 0x4c, 0xa7, 0xcb, 0x00, 0x8a, 0x66, 0x6f,
+		0xa2, 0xb6, 0xb1, 0x2e, 0xe1, 0xa0, 0x18, 0xf6, 0xad, 0xf3, 0xbd, 0xeb, 0xc7, 0xef, 0x55, 0x4f,
+		0x79, 0x91, 0x8d, 0x36, 0x13, 0x7b, 0xd0, 0x4a, 0x6c, 0x39, 0xfb, 0x53, 0xb8, 0x6f, 0x02, 0x51,
+		0xa5, 0x20, 0xac, 0x24, 0x1c, 0x73, 0x59, 0x73, 0x58, 0x61, 0x3a, 0x87, 0x58, 0xb3, 0x20, 0x56,
+		0x39, 0x06, 0x2b, 0x4d, 0xd3, 0x20, 0x2b, 0x89, 0x3f, 0xa2, 0xf0, 0x96, 0xeb, 0x7f, 0xa4, 0xcd,
+		0x11, 0xae, 0xbd, 0xcb, 0x3a, 0xb4, 0xd9, 0x91, 0x09, 0x35, 0x71, 0x50, 0x65, 0xac, 0x92, 0xe3,
+		0x7b, 0x32, 0xc0, 0x7a, 0xdd, 0xd4, 0xc3, 0x92, 0x6f, 0xeb, 0x79, 0xde, 0x6f, 0xd3, 0x25, 0xc9,
+		0xcd, 0x63, 0xf5, 0x1e, 0x7a, 0x3b, 0x26, 0x9d, 0x77, 0x04, 0x80, 0xa9, 0xbf, 0x38, 0xb5, 0xbd,
+		0xb8, 0x05, 0x07, 0xbd, 0xfd, 0xab, 0x7b, 0xf8, 0x2a, 0x26, 0xcc, 0x49, 0x14, 0x6d, 0x55, 0x01,
+		0x06, 0x94, 0xd8, 0xb2, 0x2d, 0x53, 0x83, 0x1b, 0x8f, 0xd4, 0xdd, 0x57, 0x12, 0x7e, 0x18, 0xba,
+		0x8e, 0xe2, 0x4d, 0x80, 0xef, 0x7e, 0x6b, 0x9d, 0x24, 0xa9, 0x60, 0xa4, 0x97, 0x85, 0x86, 0x2a,
+		0x01, 0x00, 0x09, 0xf1, 0xcb, 0x4a, 0x24, 0x1c, 0xd8, 0xf6, 0xe6, 0x5b, 0xe7, 0x5d, 0xf2, 0xc4,
+		0x97, 0x1c, 0x10, 0xc6, 0x4d, 0x66, 0x4f, 0x98, 0x87, 0x30, 0xac, 0xd5, 0xea, 0x73, 0x49, 0x10,
+		0x80, 0xea, 0xe5, 0x5f, 0x4d, 0x5f, 0x03, 0x33, 0x66, 0x02, 0x35, 0x3d, 0x60, 0x06, 0x36, 0x4f,
+		0x14, 0x1c, 0xd8, 0x07, 0x1f, 0x78, 0xd0, 0xf8, 0x4f, 0x6c, 0x62, 0x7c, 0x15, 0xa5, 0x7c, 0x28,
+		0x7c, 0xcc, 0xeb, 0x1f, 0xd1, 0x07, 0x90, 0x93, 0x7e, 0xc2, 0xa8, 0x3a, 0x80, 0xc0, 0xf5, 0x30,
+		0xcc, 0x75, 0xcf, 0x16, 0x26, 0xa9, 0x26, 0x3b, 0xe7, 0x68, 0x2f, 0x15, 0x21, 0x5b, 0xe4, 0x00,
+		0xbd, 0x48, 0x50, 0xcd, 0x75, 0x70, 0xc4, 0x62, 0xbb, 0x41, 0xfb, 0x89, 0x4a, 0x88, 0x3b, 0x3b,
+		0x51, 0x66, 0x02, 0x69, 0x04, 0x97, 0x36, 0xd4, 0x75, 0xae, 0x0b, 0xa3, 0x42, 0xf8, 0xca, 0x79,
+		0x8f, 0x93, 0xe9, 0xcc, 0x38, 0xbd, 0xd6, 0xd2, 0xf9, 0x70, 0x4e, 0xc3, 0x6a, 0x8e, 0x25, 0xbd,
+		0xea, 0x15, 0x5a, 0xa0, 0x85, 0x7e, 0x81, 0x0d, 0x03, 0xe7, 0x05, 0x39, 0xf5, 0x05, 0x26, 0xee,
+		0xec, 0xaa, 0x1f, 0x3d, 0xc9, 0x98, 0x76, 0x01, 0x2c, 0xf4, 0xfc, 0xa3, 0x88, 0x77, 0x38, 0xc4,
+		0x50, 0x65, 0x50, 0x6d, 0x04, 0x1f, 0xdf, 0x5a, 0xaa, 0xf2, 0x01, 0xa9, 0xc1, 0x8d, 0xee, 0xca,
+		0x47, 0x26, 0xef, 0x39, 0xb8, 0xb4, 0xf2, 0xd1, 0xd6, 0xbb, 0x1b, 0x2a, 0xc1, 0x34, 0x14, 0xcf];
+
+	let x: XTS = XTS::with_ciphers(Speck_128_128::from_key_bytes(&data_key), Speck_128_128::from_key_bytes(&tweak_key), 512);
+	let ciphertext: Vec<u8> = x.xts_encrypt_byte_array(0, &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(x.xts_decrypt_byte_array(0, &ciphertext).unwrap(), plaintext);
+}
+
+#[test]
+fn xts_speck_128_256_works() {
+	let data_key: [u8; 32] = [
+		0x27, 0x18, 0x28, 0x18, 0x28, 0x45, 0x90, 0x45, 0x23, 0x53, 0x60, 0x28, 0x74, 0x71, 0x35, 0x26,
+		0x62, 0x49, 0x77, 0x57, 0x24, 0x70, 0x93, 0x69, 0x99, 0x59, 0x57, 0x49, 0x66, 0x96, 0x76, 0x27];
+	let tweak_key: [u8; 32] = [
+		0x31, 0x41, 0x59, 0x26, 0x53, 0x58, 0x97, 0x93, 0x23, 0x84, 0x62, 0x64, 0x33, 0x83, 0x27, 0x95,
+		0x02, 0x88, 0x41, 0x97, 0x16, 0x93, 0x99, 0x37, 0x51, 0x05, 0x82, 0x09, 0x74, 0x94, 0x45, 0x92];
+	let plaintext: Vec<u8> = (0 .. 512).map(|x| x as u8).collect();
+	let expected: [u8; 512] = [
+		0xc5, 0x85, 0x2a, 0x4b, 0x73, 0xe4, 0xf6, 0xf1, 0x7e, 0xf9, 0xf6, 0xe9, 0xa3, 0x73, 0x36, 0xcb,
+		0xaa, 0xb6, 0x22, 0xb0, 0x24, 0x6e, 0x3d, 0x73, 0x92, 0x99, 0xde, 0xd3, 0x76, 0xed, 0xcd, 0x63,
+		0x64, 0x3a, 0x22, 0x57, 0xc1, 0x43, 0x49, 0xd4, 0x79, 0x36, 0x31, 0x19, 0x62, 0xae, 0x10, 0x7e,
+		0x7d, 0xcf, 0x7a, 0xe2, 0x6b, 0xce, 0x27, 0xfa, 0xdc, 0x3d, 0xd9, 0x83, 0xd3, 0x42, 0x4c, 0xe0,
+		0x1b, 0xd6, 0x1d, 0x1a, 0x6f, 0xd2, 0x03, 0x00, 0xfc, 0x81, 0x99, 0x8a, 0x14, 0x62, 0xf5, 0x7e,
+		0x0d, 0xe7, 0x12, 0xe8, 0x17, 0x9d, 0x0b, 0xec, 0xe2, 0xf7, 0xc9, 0xa7, 0x63, 0xd1, 0x79, 0xb6,
+		0x62, 0x62, 0x37, 0xfe, 0x0a, 0x4c, 0x4a, 0x37, 0x70, 0xc7, 0x5e, 0x96, 0x5f, 0xbc, 0x8e, 0x9e,
+		0x85, 0x3c, 0x4f, 0x26, 0x64, 0x85, 0xbc, 0x68, 0xb0, 0xe0, 0x86, 0x5e, 0x26, 0x41, 0xce, 0x11,
+		0x50, 0xda, 0x97, 0x14, 0xe9, 0x9e, 0xc7, 0x6d, 0x3b, 0xdc, 0x43, 0xde, 0x2b, 0x27, 0x69, 0x7d,
+		0xfc, 0xb0, 0x28, 0xbd, 0x8f, 0xb1, 0xc6, 0x31, 0x14, 0x4d, 0xf0, 0x74, 0x37, 0xfd, 0x07, 0x25,
+		0x96, 0x55, 0xe5, 0xfc, 0x9e, 0x27, 0x2a, 0x74, 0x1b, 0x83, 0x4d, 0x15, 0x83, 0xac, 0x57, 0xa0,
+		0xac, 0xa5, 0xd0, 0x38, 0xef, 0x19, 0x56, 0x53, 0x25, 0x4b, 0xfc, 0xce, 0x04, 0x23, 0xe5, 0x6b,
+		0xf6, 0xc6, 0x6c, 0x32, 0x0b, 0xb3, 0x12, 0xc5, 0xed, 0x22, 0x34, 0x1c, 0x5d, 0xed, 0x17, 0x06,
+		0x36, 0xa3, 0xe6, 0x77, 0xb9, 0x97, 0x46, 0xb8, 0xe9, 0x3f, 0x7e, 0xc7, 0xbc, 0x13, 0x5c, 0xdc,
+		0x6e, 0x3f, 0x04, 0x5e, 0xd1, 0x59, 0xa5, 0x82, 0x35, 0x91, 0x3d, 0x1b, 0xe4, 0x97, 0x9f, 0x92,
+		0x1c, 0x5e, 0x5f, 0x6f, 0x41, 0xd4, 0x62, 0xa1, 0x8d, 0x39, 0xfc, 0x42, 0xfb, 0x38, 0x80, 0xb9,
+		0x0a, 0xe3, 0xcc, 0x6a, 0x93, 0xd9, 0x7a, 0xb1, 0xe9, 0x69, 0xaf, 0x0a, 0x6b, 0x75, 0x38, 0xa7,
+		0xa1, 0xbf, 0xf7, 0xda, 0x95, 0x93, 0x4b, 0x78, 0x19, 0xf5, 0x94, 0xf9, 0xd2, 0x00, 0x33, 0x37,
+		0xcf, 0xf5, 0x9e, 0x9c, 0xf3, 0xcc, 0xa6, 0xee, 0x42, 0xb2, 0x9e, 0x2c, 0x5f, 0x48, 0x23, 0x26,
+		0x15, 0x25, 0x17, 0x03, 0x3d, 0xfe, 0x2c, 0xfc, 0xeb, 0xba, 0xda, 0xe0, 0x00, 0x05, 0xb6, 0xa6,
+		0x07, 0xb3, 0xe8, 0x36, 0x5b, 0xec, 0x5b, 0xbf, 0xd6, 0x5b, 0x00, 0x74, 0xc6, 0x97, 0xf1, 0x6a,
+		0x49, 0xa1, 0xc3, 0xfa, 0x10, 0x52, 0xb9, 0x14, 0xad, 0xb7, 0x73, 0xf8, 0x78, 0x12, 0xc8, 0x59,
+		0x17, 0x80, 0x4c, 0x57, 0x39, 0xf1, 0x6d, 0x80, 0x25, 0x77, 0x0f, 0x5e, 0x7d, 0xf0, 0xaf, 0x21,
+		0xec, 0xce, 0xb7, 0xc8, 0x02, 0x8a, 0xed, 0x53, 0x2c, 0x25, 0x68, 0x2e, 0x1f, 0x85, 0x5e, 0x67,
+		0xd1, 0x07, 0x7a, 0x3a, 0x89, 0x08, 0xe0, 0x34, 0xdc, 0xdb, 0x26, 0xb4, 0x6b, 0x77, 0xfc, 0x40,
+		0x31, 0x15, 0x72, 0xa0, 0xf0, 0x73, 0xd9, 0x3b, 0xd5, 0xdb, 0xfe, 0xfc, 0x8f, 0xa9, 0x44, 0xa2,
+		0x09, 0x9f, 0xc6, 0x33, 0xe5, 0xe2, 0x88, 0xe8, 0xf3, 0xf0, 0x1a, 0xf4, 0xce, 0x12, 0x0f, 0xd6,
+		0xf7, 0x36, 0xe6, 0xa4, 0xf4, 0x7a, 0x10, 0x58, 0xcc, 0x1f, 0x48, 0x49, 0x65, 0x47, 0x75, 0xe9,
+		0x28, 0xe1, 0x65, 0x7b, 0xf2, 0xc4, 0xb5, 0x07, 0xf2, 0xec, 0x76, 0xd8, 0x8f, 0x09, 0xf3, 0x16,
+		0xa1, 0x51, 0x89, 0x3b, 0xeb, 0x96, 0x42, 0xac, 0x65, 0xe0, 0x67, 0x63, 0x29, 0xdc, 0xb4, 0x7d,
+		0xf2, 0x41, 0x51, 0x6a, 0xcb, 0xde, 0x3c, 0xfb, 0x66, 0x8d, 0x13, 0xca, 0xe0, 0x59, 0x2a, 0x00,
+		0xc9, 0x53, 0x4c, 0xe6, 0x9e, 0xe2, 0x73, 0xd5, 0x67, 0x19, 0xb2, 0xbd, 0x9a, 0x63, 0xd7, 0x5c];
+
+	let x: XTS<Speck_128_256> = XTS::with_ciphers(Speck_128_256::from_key_bytes(&data_key), Speck_128_256::from_key_bytes(&tweak_key), 512);
+	let ciphertext: Vec<u8> = x.xts_encrypt_byte_array(0xff, &plaintext).unwrap();
+	assert_eq!(ciphertext.as_slice(), &expected[..]);
+	assert_eq!(x.xts_decrypt_byte_array(0xff, &ciphertext).unwrap(), plaintext);
+}
+
+// The kernel's xts template had no ciphertext stealing while it carried Speck, so there are no
+// vectors for it. These are regression values of this code with the keys and tweak of P1619 vectors 15-18.
+#[test]
+fn xts_ciphertext_stealing() {
+	let data_key: [u8; 16]  = [0xff, 0xfe, 0xfd, 0xfc, 0xfb, 0xfa, 0xf9, 0xf8, 0xf7, 0xf6, 0xf5, 0xf4, 0xf3, 0xf2, 0xf1, 0xf0];
+	let tweak_key: [u8; 16] = [0xbf, 0xbe, 0xbd, 0xbc, 0xbb, 0xba, 0xb9, 0xb8, 0xb7, 0xb6, 0xb5, 0xb4, 0xb3, 0xb2, 0xb1, 0xb0];
+	let plaintext: Vec<u8> = (0..31).collect();
+	let expected: [(usize, Vec<u8>); 3] = [
+		(17, vec![
+			0xc9, 0xc8, 0xa6, 0xf3, 0x77, 0x09, 0x4b, 0xf0, 0x30, 0x9f, 0xa5, 0xc0, 0xd9, 0xc8, 0x55, 0x87,
+			0x7f]),
+		(20, vec![
+			0xf0, 0x8e, 0xe6, 0xab, 0x76, 0xef, 0x0c, 0x9a, 0x3e, 0xf3, 0x74, 0xb4, 0xba, 0x11, 0xbf, 0x52,
+			0x7f, 0xe4, 0xf0, 0x67]),
+		(31, vec![
+			0xb6, 0xcc, 0xb7, 0x22, 0x2b, 0x04, 0xe6, 0x41, 0x7c, 0xfa, 0xd8, 0x76, 0x6c, 0x32, 0x63, 0xc9,
+			0x7f, 0xe4, 0xf0, 0x67, 0xf4, 0x03, 0xe0, 0xec, 0x3d, 0x26, 0xf5, 0xde, 0xe2, 0x64, 0xb2])];
+
+	let x: XTS = XTS::with_ciphers(Speck_128_128::from_key_bytes(&data_key), Speck_128_128::from_key_bytes(&tweak_key), 512);
+
+	for &(len, ref ciphertext) in expected.iter() {
+		assert_eq!(&x.xts_encrypt_byte_array(0x9a78563412, &plaintext[..len]).unwrap(), ciphertext);
+		assert_eq!(x.xts_decrypt_byte_array(0x9a78563412, ciphertext).unwrap(), &plaintext[..len]);
+	}
+}
+
+#[test]
+fn xts_data_units() {
+	let data_key: [u64; 2]  = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+	let tweak_key: [u64; 2] = [0x1716151413121110, 0x1f1e1d1c1b1a1918];
+
+	let x: XTS = XTS::new(&data_key, &tweak_key, 64);
+	let plaintext: Vec<u8> = (0 .. 210).map(|x| x as u8).collect();
+
+	//Every data unit is encrypted with its own sector number
+	let ciphertext: Vec<u8> = x.xts_encrypt_byte_array(7, &plaintext).unwrap();
+	for (i, data_unit) in plaintext.chunks(64).enumerate() {
+		let mut expected: Vec<u8> = data_unit.to_vec();
+		x.encrypt_sector(7 + i as u64, &mut expected).unwrap();
+		assert_eq!(&ciphertext[i * 64 .. i * 64 + data_unit.len()], expected.as_slice());
+	}
+	assert_eq!(x.xts_decrypt_byte_array(7, &ciphertext).unwrap(), plaintext);
+
+	//The last unit is shorter than a block
+	assert!(x.xts_encrypt_byte_array(0, &plaintext[..140]).is_err());
+	assert!(x.xts_encrypt_byte_array(0, &plaintext[..15]).is_err());
+	assert!(x.encrypt_sector(0, &mut [0; 65]).is_err());
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 const S0: [u8; 256] = [
 	0x3e,0x72,0x5b,0x47,0xca,0xe0,0x00,0x33,0x04,0xd1,0x54,0x98,0x09,0xb9,0x6d,0xcb,
 	0x7b,0x1b,0xf9,0x32,0xaf,0x9d,0x6a,0xa5,0xb8,0x2d,0xfc,0x1d,0x08,0x53,0x03,0x90,
@@ -63,35 +65,204 @@ impl LfsrBuffer {
 	}
 }
 
+pub const STATE_VERSION: u8 = 1;
+// version, variant, key check, LFSR, F registers, position, buffered bytes
+const VARIANT_OFFSET: usize = 1;
+const KEY_CHECK_OFFSET: usize = VARIANT_OFFSET + 1;
+const LFSR_OFFSET: usize = KEY_CHECK_OFFSET + 8;
+const F_R_OFFSET: usize = LFSR_OFFSET + 16 * 4;
+const POSITION_OFFSET: usize = F_R_OFFSET + 2 * 4;
+const BYTE_POS_OFFSET: usize = POSITION_OFFSET + 8;
+const BYTE_BUFF_OFFSET: usize = BYTE_POS_OFFSET + 1;
+const STATE_LEN: usize = BYTE_BUFF_OFFSET + 4;
+
+#[derive(Debug, PartialEq)]
+pub enum ZucStateError {
+	WrongLength,
+	UnsupportedVersion,
+	InvalidState,
+	// Saved by ZUC-256 and resumed by ZUC-128, or the other way round
+	WrongCipher,
+	// Key or IV given on resume are not the ones the state was saved with
+	WrongKey,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ZucVariant {
+	Zuc128 = 1,
+	Zuc256 = 2,
+}
+
+// Snapshot of a running ZUC, brc_x is not kept since it is recomputed before every use.
+// LFSR cells are ordered from s0 to s15.
+// The key and IV are not stored, but the state is still secret: ZUC can be run backwards
+// from it to the loaded key, so the encoded state must be protected like the key itself.
+#[derive(Clone, PartialEq)]
+pub struct ZucState {
+	variant: ZucVariant,
+	key_check: u64,
+	lfsr: [u32; 16],
+	f_r1: u32,
+	f_r2: u32,
+	position: u64,
+	byte_pos: usize,
+	byte_buff: [u8; 4],
+}
+
+// Only the variant and the position are printed
+impl fmt::Debug for ZucState {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ZucState").field("variant", &self.variant).field("position", &self.position).finish_non_exhaustive()
+	}
+}
+
+impl ZucState {
+	// All integers are big-endian, the first byte is STATE_VERSION.
+	// The result is key material, see ZucState.
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut result: Vec<u8> = Vec::with_capacity(STATE_LEN);
+		result.push(STATE_VERSION);
+		result.push(self.variant as u8);
+		result.extend_from_slice(&self.key_check.to_be_bytes());
+		for x in self.lfsr.iter() {
+			result.extend_from_slice(&x.to_be_bytes());
+		}
+		result.extend_from_slice(&self.f_r1.to_be_bytes());
+		result.extend_from_slice(&self.f_r2.to_be_bytes());
+		result.extend_from_slice(&self.position.to_be_bytes());
+		result.push(self.byte_pos as u8);
+		result.extend_from_slice(&self.byte_buff);
+		result
+	}
+
+	pub fn from_bytes(bytes: &[u8]) -> Result<ZucState, ZucStateError> {
+		if bytes.is_empty() { return Err(ZucStateError::WrongLength) };
+		if bytes[0] != STATE_VERSION { return Err(ZucStateError::UnsupportedVersion) };
+		if bytes.len() != STATE_LEN { return Err(ZucStateError::WrongLength) };
+
+		let word_at = |offset: usize| u32::from_be_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]]);
+		let mut lfsr: [u32; 16] = [0; 16];
+		for (i, x) in lfsr.iter_mut().enumerate() {
+			*x = word_at(LFSR_OFFSET + 4 * i);
+		}
+		let variant: ZucVariant = match bytes[VARIANT_OFFSET] {
+			1 => ZucVariant::Zuc128,
+			2 => ZucVariant::Zuc256,
+			_ => return Err(ZucStateError::InvalidState),
+		};
+		let mut key_check: [u8; 8] = [0; 8];
+		key_check.copy_from_slice(&bytes[KEY_CHECK_OFFSET..LFSR_OFFSET]);
+		let mut position: [u8; 8] = [0; 8];
+		position.copy_from_slice(&bytes[POSITION_OFFSET..BYTE_POS_OFFSET]);
+		let mut byte_buff: [u8; 4] = [0; 4];
+		byte_buff.copy_from_slice(&bytes[BYTE_BUFF_OFFSET..STATE_LEN]);
+
+		let state: ZucState = ZucState {variant, key_check: u64::from_be_bytes(key_check), lfsr,
+		                                f_r1: word_at(F_R_OFFSET), f_r2: word_at(F_R_OFFSET + 4),
+		                                position: u64::from_be_bytes(position), byte_pos: bytes[BYTE_POS_OFFSET] as usize, byte_buff};
+
+		// LFSR cells are elements of GF(2^31 - 1)
+		if !state.lfsr.iter().all(|&x| x >> 31 == 0) || state.byte_pos > 4 {
+			return Err(ZucStateError::InvalidState);
+		}
+		Ok(state)
+	}
+}
+
 #[allow(non_camel_case_types)]
 pub struct Zuc_128 {
 	lfsr: LfsrBuffer,
 	brc_x: [u32; 4],
 	f_r1: u32,
 	f_r2: u32,
+	// Keystream bytes left over from the last word used by apply_keystream
+	byte_buff: [u8; 4],
+	byte_pos: usize,
+	// Key and IV as loaded into the LFSR, kept to rewind the stream
+	loaded_lfsr: [u32; 16],
+	position: u64,
 }
 
 impl Zuc_128 {
 	pub fn new(k: &[u8; 16], iv: &[u8; 16]) -> Zuc_128 {
-		let mut lfsr_arr: [u32; 16] = [0u32; 16];	// No need to init here, but compiler is grumbling
-		for i in 0..16 {
-			lfsr_arr[i] = make_u31(k[i], D[i], iv[i]);
-		}
+		Zuc_128::with_lfsr(load_lfsr(k, iv))
+	}
 
+	// Runs the initialisation rounds on loaded LFSR cells, shared with ZUC-256
+	pub(crate) fn with_lfsr(lfsr_arr: [u32; 16]) -> Zuc_128 {
 		let lfsr_buffer: LfsrBuffer = LfsrBuffer::new(lfsr_arr);
-		let mut result: Zuc_128 = Zuc_128 {lfsr: lfsr_buffer, brc_x: [0u32; 4], f_r1: 0, f_r2: 0};
+		let mut result: Zuc_128 = Zuc_128 {lfsr: lfsr_buffer, brc_x: [0u32; 4], f_r1: 0, f_r2: 0,
+		                                   byte_buff: [0u8; 4], byte_pos: 4, loaded_lfsr: lfsr_arr, position: 0};
+		result.initialize();
+		result
+	}
+
+	fn initialize(&mut self) {
+		self.lfsr = LfsrBuffer::new(self.loaded_lfsr);
+		self.f_r1 = 0;
+		self.f_r2 = 0;
+		self.byte_pos = 4;
+		self.position = 0;
 
 		for _i in 0..32 {
-			result.bit_reorganization();
+			self.bit_reorganization();
 			//println!("brc_x{} {:x}\t{:x}\t{:x}\t{:x}", _i,
-			//	result.brc_x[0], result.brc_x[1], result.brc_x[2], result.brc_x[3]);
-			let w: u32 = result.f();
-			//println!("f_r{}   {:x}\t{:x}", _i, result.f_r1, result.f_r2);
-			result.lfsr_with_init_mode(w >> 1);
-			//println!("lfsr{}  {:x}\t{:x}", _i, result.lfsr.get(0), result.lfsr.get(1));
+			//	self.brc_x[0], self.brc_x[1], self.brc_x[2], self.brc_x[3]);
+			let w: u32 = self.f();
+			//println!("f_r{}   {:x}\t{:x}", _i, self.f_r1, self.f_r2);
+			self.lfsr_with_init_mode(w >> 1);
+			//println!("lfsr{}  {:x}\t{:x}", _i, self.lfsr.get(0), self.lfsr.get(1));
 		}
 
-		result
+		// The first output of F in working mode is discarded, once per key/IV
+		self.bit_reorganization();
+		let _unused = self.f();
+		self.lfsr_with_work_mode();
+	}
+
+	pub fn save_state(&self) -> ZucState {
+		self.save_state_as(ZucVariant::Zuc128)
+	}
+
+	pub(crate) fn save_state_as(&self, variant: ZucVariant) -> ZucState {
+		let mut lfsr: [u32; 16] = [0; 16];
+		for (i, x) in lfsr.iter_mut().enumerate() {
+			*x = self.lfsr.get(i);
+		}
+		ZucState {variant, key_check: key_check(self.loaded_lfsr), lfsr, f_r1: self.f_r1, f_r2: self.f_r2,
+		          position: self.position, byte_pos: self.byte_pos, byte_buff: self.byte_buff}
+	}
+
+	// Continues the stream exactly where the saved one stopped.
+	// The state does not hold the key and IV, they are needed again to rewind with seek_to_word,
+	// so they are checked against the state.
+	pub fn from_state(state: &ZucState, k: &[u8; 16], iv: &[u8; 16]) -> Result<Zuc_128, ZucStateError> {
+		Zuc_128::with_state(state, ZucVariant::Zuc128, load_lfsr(k, iv))
+	}
+
+	pub(crate) fn with_state(state: &ZucState, variant: ZucVariant, loaded_lfsr: [u32; 16]) -> Result<Zuc_128, ZucStateError> {
+		if state.variant != variant { return Err(ZucStateError::WrongCipher) };
+		if state.key_check != key_check(loaded_lfsr) { return Err(ZucStateError::WrongKey) };
+
+		Ok(Zuc_128 {lfsr: LfsrBuffer::new(state.lfsr), brc_x: [0u32; 4], f_r1: state.f_r1, f_r2: state.f_r2,
+		            byte_buff: state.byte_buff, byte_pos: state.byte_pos, loaded_lfsr, position: state.position})
+	}
+
+	// Number of keystream words produced so far, a partially used word counts as produced
+	pub fn position(&self) -> u64 {
+		self.position
+	}
+
+	// Next word produced is the word number n of the stream.
+	// Going back re-initialises from the saved key and IV.
+	pub fn seek_to_word(&mut self, n: u64) {
+		if n < self.position {
+			self.initialize();
+		}
+		while self.position < n {
+			self.next_word();
+		}
+		self.byte_pos = 4;
 	}
 
 	#[inline]
@@ -140,45 +311,88 @@ impl Zuc_128 {
 		let w2: u32 = self.f_r2 ^ self.brc_x[2];
 		let u: u32 = l1((w1 << 16) | (w2 >> 16));
 		let v: u32 = l2((w2 << 16) | (w1 >> 16));
-		self.f_r1 = make_u32(S0[(u >> 24) as usize],
-		                     S1[((u >> 16) & 0xFF) as usize],
-		                     S0[((u >> 8) & 0xFF) as usize],
-		                     S1[(u & 0xFF) as usize]);
-		self.f_r2 = make_u32(S0[(v >> 24) as usize],
-		                     S1[((v >> 16) & 0xFF) as usize],
-		                     S0[((v >> 8) & 0xFF) as usize],
-		                     S1[(v & 0xFF) as usize]);
+		self.f_r1 = s_box(u);
+		self.f_r2 = s_box(v);
 		return w;
 	}
 
+	fn next_word(&mut self) -> u32 {
+		self.bit_reorganization();
+		let z: u32 = self.f() ^ self.brc_x[3];
+		self.lfsr_with_work_mode();
+		self.position += 1;
+		z
+	}
+
+	// Continues the stream from the next whole word, bytes buffered by apply_keystream are dropped
 	pub fn generate_keystream(&mut self, buffer: &mut[u32], length: usize) {
-		{
-			self.bit_reorganization();
-			let _unused = self.f();
-			self.lfsr_with_work_mode();
+		for x in buffer[..length].iter_mut() {
+			*x = self.next().unwrap();
 		}
-		for i in 0..length {
-			self.bit_reorganization();
-			buffer[i] = self.f() ^ self.brc_x[3];
-			self.lfsr_with_work_mode();
+	}
+
+	// XORs data with the keystream, words are consumed big-endian.
+	// Calls can be chunked arbitrarily, the output is the same as for one call.
+	pub fn apply_keystream(&mut self, data: &mut [u8]) {
+		for x in data.iter_mut() {
+			if self.byte_pos == 4 {
+				self.byte_buff = self.next_word().to_be_bytes();
+				self.byte_pos = 0;
+			}
+			*x ^= self.byte_buff[self.byte_pos];
+			self.byte_pos += 1;
 		}
 	}
 }
 
+// Endless stream of keystream words, continues from the next whole word like generate_keystream
+impl Iterator for Zuc_128 {
+	type Item = u32;
+
+	fn next(&mut self) -> Option<u32> {
+		self.byte_pos = 4;
+		Some(self.next_word())
+	}
+}
+
+// First two keystream words of the loaded key and IV. They tell whether a state was saved
+// with the same key and IV, and reveal nothing that the state itself doesn't.
+fn key_check(loaded_lfsr: [u32; 16]) -> u64 {
+	let mut zuc: Zuc_128 = Zuc_128::with_lfsr(loaded_lfsr);
+	((zuc.next_word() as u64) << 32) | (zuc.next_word() as u64)
+}
+
+// Key loading of ZUC-128, shared with the multi-lane engine
+pub(crate) fn load_lfsr(k: &[u8; 16], iv: &[u8; 16]) -> [u32; 16] {
+	let mut lfsr_arr: [u32; 16] = [0u32; 16];	// No need to init here, but compiler is grumbling
+	for i in 0..16 {
+		lfsr_arr[i] = make_u31(k[i], D[i], iv[i]);
+	}
+	lfsr_arr
+}
+
+#[inline]
+pub(crate) fn s_box(x: u32) -> u32 {
+	make_u32(S0[(x >> 24) as usize],
+	         S1[((x >> 16) & 0xFF) as usize],
+	         S0[((x >> 8) & 0xFF) as usize],
+	         S1[(x & 0xFF) as usize])
+}
+
 // c = (a + b) mod (2^31 – 1)
 #[inline]
-fn add_mod(a: u32, b: u32) -> u32 {
+pub(crate) fn add_mod(a: u32, b: u32) -> u32 {
 	let c: u32 = a.wrapping_add(b);
 	(c & 0x7FFFFFFF) + (c >> 31)
 }
 
 #[inline]
-fn mul_by_pow_2 (x: u32, k: usize) -> u32 {
+pub(crate) fn mul_by_pow_2 (x: u32, k: usize) -> u32 {
 	((((x) << k) | ((x) >> (31 - k))) & 0x7FFFFFFF)
 }
 
 #[inline]
-fn l1(x: u32) -> u32 {
+pub(crate) fn l1(x: u32) -> u32 {
 	let x2  = x.rotate_left(2);
 	let x10 = x.rotate_left(10);
 	let x18 = x.rotate_left(18);
@@ -187,7 +401,7 @@ fn l1(x: u32) -> u32 {
 }
 
 #[inline]
-fn l2(x: u32) -> u32 {
+pub(crate) fn l2(x: u32) -> u32 {
 	let x8  = x.rotate_left(8);
 	let x14 = x.rotate_left(14);
 	let x22 = x.rotate_left(22);
@@ -322,4 +536,171 @@ fn zuc_128_test_set_4_2() {
 	for i in 0..25 {
 		assert_eq!(plaintext[i] ^ keystream[i], ciphertext[i]);
 	}
-}
\ No newline at end of file
+}
+
+#[test]
+fn zuc_128_chunked_keystream() {
+	let key: [u8; 16] = [0x3d, 0x4c, 0x4b, 0xe9, 0x6a, 0x82, 0xfd, 0xae,
+	                     0xb5, 0x8f, 0x64, 0x1d, 0xb1, 0x7b, 0x45, 0x5b];
+	let iv: [u8; 16]  = [0x84, 0x31, 0x9a, 0xa8, 0xde, 0x69, 0x15, 0xca,
+	                     0x1f, 0x6b, 0xda, 0x6b, 0xfb, 0xd8, 0xc7, 0x66];
+
+	let mut one_shot: [u32; 10] = [0u32; 10];
+	Zuc_128::new(&key, &iv).generate_keystream(&mut one_shot, 10);
+
+	//Words do not depend on how the calls are split
+	let mut chunked: [u32; 10] = [0u32; 10];
+	let mut zuc: Zuc_128 = Zuc_128::new(&key, &iv);
+	zuc.generate_keystream(&mut chunked[..3], 3);
+	zuc.generate_keystream(&mut chunked[3..4], 1);
+	zuc.generate_keystream(&mut chunked[4..], 6);
+	assert_eq!(chunked, one_shot);
+
+	//Neither do bytes
+	let mut data: Vec<u8> = (0..40).collect();
+	let mut zuc: Zuc_128 = Zuc_128::new(&key, &iv);
+	zuc.apply_keystream(&mut data);
+	let expected: Vec<u8> = one_shot.iter().flat_map(|w| w.to_be_bytes()).zip(0..40).map(|(k, m)| k ^ m).collect();
+	assert_eq!(data, expected);
+
+	for &chunk_len in &[1, 3, 5, 7] {
+		let mut zuc: Zuc_128 = Zuc_128::new(&key, &iv);
+		let mut chunked: Vec<u8> = (0..40).collect();
+		for chunk in chunked.chunks_mut(chunk_len) {
+			zuc.apply_keystream(chunk);
+		}
+		assert_eq!(chunked, expected);
+	}
+}
+
+#[test]
+fn zuc_128_apply_keystream_test_set_4_1() {
+	let key: [u8; 16] = [0x17, 0x3d, 0x14, 0xba, 0x50, 0x03, 0x73, 0x1d,
+	                     0x7a, 0x60, 0x04, 0x94, 0x70, 0xf0, 0x0a, 0x29];
+	let iv: [u8; 16]  = [0x66, 0x03, 0x54, 0x92, 0x78, 0x00, 0x00, 0x00,
+	                     0x66, 0x03, 0x54, 0x92, 0x78, 0x00, 0x00, 0x00];
+	let plaintext: [u8; 24] = [0x6c, 0xf6, 0x53, 0x40, 0x73, 0x55, 0x52, 0xab, 0x0c, 0x97, 0x52, 0xfa,
+	                           0x6f, 0x90, 0x25, 0xfe, 0x0b, 0xd6, 0x75, 0xd9, 0x00, 0x58, 0x75, 0xb2];
+	let ciphertext: [u8; 24] = [0xa6, 0xc8, 0x5f, 0xc6, 0x6a, 0xfb, 0x85, 0x33, 0xaa, 0xfc, 0x25, 0x18,
+	                            0xdf, 0xe7, 0x84, 0x94, 0x0e, 0xe1, 0xe4, 0xb0, 0x30, 0x23, 0x8c, 0xc8];
+
+	let mut data: [u8; 24] = plaintext;
+	let mut zuc: Zuc_128 = Zuc_128::new(&key, &iv);
+	zuc.apply_keystream(&mut data[..10]);
+	zuc.apply_keystream(&mut data[10..]);
+	assert_eq!(data, ciphertext);
+}
+
+#[test]
+fn zuc_128_iterator_and_seek() {
+	let key: [u8; 16] = [0x4d, 0x32, 0x0b, 0xfa, 0xd4, 0xc2, 0x85, 0xbf,
+	                     0xd6, 0xb8, 0xbd, 0x00, 0xf3, 0x9d, 0x8b, 0x41];
+	let iv: [u8; 16]  = [0x52, 0x95, 0x9d, 0xab, 0xa0, 0xbf, 0x17, 0x6e,
+	                     0xce, 0x2d, 0xc3, 0x15, 0x04, 0x9e, 0xb5, 0x74];
+
+	let mut zuc: Zuc_128 = Zuc_128::new(&key, &iv);
+	let keystream: Vec<u32> = zuc.by_ref().take(2000).collect();
+	assert_eq!(keystream[0], 0xed4400e7);
+	assert_eq!(keystream[1], 0x0633e5c5);
+	assert_eq!(keystream[1999], 0x7a574cdb);
+	assert_eq!(zuc.position(), 2000);
+
+	//Backward and forward random access
+	for &n in &[1999, 0, 1, 1000, 1001, 500] {
+		zuc.seek_to_word(n);
+		assert_eq!(zuc.position(), n);
+		assert_eq!(zuc.next(), Some(keystream[n as usize]));
+	}
+
+	//Seeking drops a partially used word
+	let mut data: [u8; 6] = [0; 6];
+	zuc.seek_to_word(7);
+	zuc.apply_keystream(&mut data[..1]);
+	assert_eq!(zuc.position(), 8);
+	zuc.seek_to_word(7);
+	zuc.apply_keystream(&mut data[1..5]);
+	assert_eq!(data[1..5], keystream[7].to_be_bytes());
+}
+
+#[test]
+fn zuc_128_save_and_restore() {
+	let key: [u8; 16] = [0x3d, 0x4c, 0x4b, 0xe9, 0x6a, 0x82, 0xfd, 0xae,
+	                     0xb5, 0x8f, 0x64, 0x1d, 0xb1, 0x7b, 0x45, 0x5b];
+	let iv: [u8; 16]  = [0x84, 0x31, 0x9a, 0xa8, 0xde, 0x69, 0x15, 0xca,
+	                     0x1f, 0x6b, 0xda, 0x6b, 0xfb, 0xd8, 0xc7, 0x66];
+
+	let mut expected: Vec<u8> = vec![0; 100];
+	Zuc_128::new(&key, &iv).apply_keystream(&mut expected);
+
+	//Pause in the middle of a word, resume from the encoded state
+	let mut data: Vec<u8> = vec![0; 100];
+	let mut zuc: Zuc_128 = Zuc_128::new(&key, &iv);
+	zuc.apply_keystream(&mut data[..37]);
+	let encoded: Vec<u8> = zuc.save_state().to_bytes();
+	let state: ZucState = ZucState::from_bytes(&encoded).unwrap();
+	assert_eq!(state, zuc.save_state());
+	assert_eq!(encoded.len(), STATE_LEN);
+	assert_eq!(format!("{:?}", state), "ZucState { variant: Zuc128, position: 10, .. }");
+
+	let mut resumed: Zuc_128 = Zuc_128::from_state(&state, &key, &iv).unwrap();
+	assert_eq!(resumed.position(), 10);
+	resumed.apply_keystream(&mut data[37..]);
+	assert_eq!(data, expected);
+
+	//The restored stream can still be rewound
+	resumed.seek_to_word(0);
+	let mut words: [u32; 2] = [0; 2];
+	resumed.generate_keystream(&mut words, 2);
+	assert_eq!(words, [0x14f1c272, 0x3279c419]);
+}
+
+#[test]
+fn zuc_128_state_errors() {
+	let encoded: Vec<u8> = Zuc_128::new(&[0; 16], &[0; 16]).save_state().to_bytes();
+	assert_eq!(encoded.len(), STATE_LEN);
+
+	assert_eq!(ZucState::from_bytes(&[]), Err(ZucStateError::WrongLength));
+	assert_eq!(ZucState::from_bytes(&encoded[..STATE_LEN - 1]), Err(ZucStateError::WrongLength));
+
+	let mut wrong: Vec<u8> = encoded.clone();
+	wrong[0] = STATE_VERSION + 1;
+	assert_eq!(ZucState::from_bytes(&wrong), Err(ZucStateError::UnsupportedVersion));
+
+	let mut wrong: Vec<u8> = encoded.clone();
+	wrong[LFSR_OFFSET] = 0x80;
+	assert_eq!(ZucState::from_bytes(&wrong), Err(ZucStateError::InvalidState));
+
+	let mut wrong: Vec<u8> = encoded.clone();
+	wrong[BYTE_POS_OFFSET] = 5;
+	assert_eq!(ZucState::from_bytes(&wrong), Err(ZucStateError::InvalidState));
+
+	let mut wrong: Vec<u8> = encoded.clone();
+	wrong[VARIANT_OFFSET] = 3;
+	assert_eq!(ZucState::from_bytes(&wrong), Err(ZucStateError::InvalidState));
+}
+
+#[test]
+fn zuc_128_state_key_check() {
+	let key: [u8; 16] = [0x3d, 0x4c, 0x4b, 0xe9, 0x6a, 0x82, 0xfd, 0xae,
+	                     0xb5, 0x8f, 0x64, 0x1d, 0xb1, 0x7b, 0x45, 0x5b];
+	let iv: [u8; 16]  = [0x84, 0x31, 0x9a, 0xa8, 0xde, 0x69, 0x15, 0xca,
+	                     0x1f, 0x6b, 0xda, 0x6b, 0xfb, 0xd8, 0xc7, 0x66];
+
+	let mut zuc: Zuc_128 = Zuc_128::new(&key, &iv);
+	zuc.seek_to_word(10);
+	let state: ZucState = zuc.save_state();
+
+	//Without the check a wrong key would only show after seek_to_word rewinds into another stream
+	let mut other_key: [u8; 16] = key;
+	other_key[15] ^= 0x01;
+	assert_eq!(Zuc_128::from_state(&state, &other_key, &iv).err(), Some(ZucStateError::WrongKey));
+	let mut other_iv: [u8; 16] = iv;
+	other_iv[0] ^= 0x80;
+	assert_eq!(Zuc_128::from_state(&state, &key, &other_iv).err(), Some(ZucStateError::WrongKey));
+
+	//Key check is part of the encoding
+	let mut wrong: Vec<u8> = state.to_bytes();
+	wrong[KEY_CHECK_OFFSET] ^= 0x01;
+	let wrong_state: ZucState = ZucState::from_bytes(&wrong).unwrap();
+	assert_eq!(Zuc_128::from_state(&wrong_state, &key, &iv).err(), Some(ZucStateError::WrongKey));
+}
@@ -0,0 +1,271 @@
+// Four independent ZUC-128 streams generated in lockstep, one per SIMD lane.
+// Every lane gives exactly the same keystream as Zuc_128 with its key and IV.
+// On x86_64 the rounds use SSE2, which is always available there, elsewhere plain loops.
+
+use zuc_128::{load_lfsr, s_box, add_mod, mul_by_pow_2, l1, l2};
+
+pub const LANES: usize = 4;
+
+#[allow(non_camel_case_types)]
+pub struct Zuc_128_x4 {
+	// LFSR cell i of every lane is lfsr[(start + i) % 16]
+	lfsr: [[u32; LANES]; 16],
+	start: usize,
+	f_r1: [u32; LANES],
+	f_r2: [u32; LANES],
+	use_simd: bool,
+}
+
+impl Zuc_128_x4 {
+	pub fn new(keys: &[[u8; 16]; LANES], ivs: &[[u8; 16]; LANES]) -> Zuc_128_x4 {
+		Zuc_128_x4::with_backend(keys, ivs, cfg!(target_arch = "x86_64"))
+	}
+
+	fn with_backend(keys: &[[u8; 16]; LANES], ivs: &[[u8; 16]; LANES], use_simd: bool) -> Zuc_128_x4 {
+		let mut lfsr: [[u32; LANES]; 16] = [[0; LANES]; 16];
+		for lane in 0..LANES {
+			for (cell, &x) in lfsr.iter_mut().zip(load_lfsr(&keys[lane], &ivs[lane]).iter()) {
+				cell[lane] = x;
+			}
+		}
+
+		let mut result: Zuc_128_x4 = Zuc_128_x4 {lfsr, start: 0, f_r1: [0; LANES], f_r2: [0; LANES],
+		                                         use_simd};
+		result.initialize();
+		result
+	}
+
+	fn initialize(&mut self) {
+		for _i in 0..32 {
+			self.round(true);
+		}
+		// The first output in working mode is discarded as in Zuc_128
+		self.round(false);
+	}
+
+	// Next keystream word of every lane
+	pub fn next_words(&mut self) -> [u32; LANES] {
+		self.round(false)
+	}
+
+	// Fills the first length words of every lane's buffer
+	pub fn generate_keystream(&mut self, mut buffers: [&mut [u32]; LANES], length: usize) {
+		for lane in buffers.iter() {
+			assert!(lane.len() >= length, "Every buffer must have {0} words!", length);
+		}
+		for i in 0..length {
+			for (lane, z) in buffers.iter_mut().zip(self.next_words().iter()) {
+				lane[i] = *z;
+			}
+		}
+	}
+
+	fn round(&mut self, init_mode: bool) -> [u32; LANES] {
+		if self.use_simd {
+			self.round_simd(init_mode)
+		} else {
+			self.round_scalar(init_mode)
+		}
+	}
+
+	#[cfg(target_arch = "x86_64")]
+	fn round_simd(&mut self, init_mode: bool) -> [u32; LANES] {
+		unsafe { sse2::round(self, init_mode) }
+	}
+
+	#[cfg(not(target_arch = "x86_64"))]
+	fn round_simd(&mut self, init_mode: bool) -> [u32; LANES] {
+		self.round_scalar(init_mode)
+	}
+
+	fn cell(&self, i: usize) -> &[u32; LANES] {
+		&self.lfsr[(self.start + i) % 16]
+	}
+
+	fn push(&mut self, cells: [u32; LANES]) {
+		self.lfsr[self.start] = cells;
+		self.start = (self.start + 1) % 16;
+	}
+
+	// Bit reorganisation, F and the LFSR update of Zuc_128, lane by lane.
+	// Returns the keystream words, which are meaningless in the initialisation mode.
+	fn round_scalar(&mut self, init_mode: bool) -> [u32; LANES] {
+		let mut z: [u32; LANES] = [0; LANES];
+		let mut cells: [u32; LANES] = [0; LANES];
+		for lane in 0..LANES {
+			let s = |i: usize| self.cell(i)[lane];
+			let x0: u32 = ((s(15) & 0x7FFF8000) << 1) | (s(14) & 0xFFFF);
+			let x1: u32 = ((s(11) & 0xFFFF) << 16) | (s(9) >> 15);
+			let x2: u32 = ((s(7) & 0xFFFF) << 16) | (s(5) >> 15);
+			let x3: u32 = ((s(2) & 0xFFFF) << 16) | (s(0) >> 15);
+
+			let r1: u32 = self.f_r1[lane];
+			let r2: u32 = self.f_r2[lane];
+			let w: u32 = (x0 ^ r1).wrapping_add(r2);
+			let w1: u32 = r1.wrapping_add(x1);
+			let w2: u32 = r2 ^ x2;
+			z[lane] = w ^ x3;
+
+			let mut f: u32 = s(0);
+			f = add_mod(f, mul_by_pow_2(s(0), 8));
+			f = add_mod(f, mul_by_pow_2(s(4), 20));
+			f = add_mod(f, mul_by_pow_2(s(10), 21));
+			f = add_mod(f, mul_by_pow_2(s(13), 17));
+			f = add_mod(f, mul_by_pow_2(s(15), 15));
+			if init_mode {
+				f = add_mod(f, w >> 1);
+			}
+			cells[lane] = f;
+
+			self.f_r1[lane] = s_box(l1((w1 << 16) | (w2 >> 16)));
+			self.f_r2[lane] = s_box(l2((w2 << 16) | (w1 >> 16)));
+		}
+		self.push(cells);
+		z
+	}
+}
+
+#[cfg(target_arch = "x86_64")]
+mod sse2 {
+	use std::arch::x86_64::*;
+	use super::{Zuc_128_x4, LANES};
+	use zuc_128::s_box;
+
+	#[inline]
+	unsafe fn load(x: &[u32; LANES]) -> __m128i {
+		_mm_loadu_si128(x.as_ptr() as *const __m128i)
+	}
+
+	#[inline]
+	unsafe fn store(x: __m128i) -> [u32; LANES] {
+		let mut result: [u32; LANES] = [0; LANES];
+		_mm_storeu_si128(result.as_mut_ptr() as *mut __m128i, x);
+		result
+	}
+
+	#[inline]
+	unsafe fn rotate_left(x: __m128i, k: i32) -> __m128i {
+		_mm_or_si128(_mm_sll_epi32(x, _mm_cvtsi32_si128(k)), _mm_srl_epi32(x, _mm_cvtsi32_si128(32 - k)))
+	}
+
+	#[inline]
+	unsafe fn l1(x: __m128i) -> __m128i {
+		let t: __m128i = _mm_xor_si128(x, rotate_left(x, 2));
+		let t: __m128i = _mm_xor_si128(t, rotate_left(x, 10));
+		let t: __m128i = _mm_xor_si128(t, rotate_left(x, 18));
+		_mm_xor_si128(t, rotate_left(x, 24))
+	}
+
+	#[inline]
+	unsafe fn l2(x: __m128i) -> __m128i {
+		let t: __m128i = _mm_xor_si128(x, rotate_left(x, 8));
+		let t: __m128i = _mm_xor_si128(t, rotate_left(x, 14));
+		let t: __m128i = _mm_xor_si128(t, rotate_left(x, 22));
+		_mm_xor_si128(t, rotate_left(x, 30))
+	}
+
+	// c = (a + b) mod (2^31 – 1)
+	#[inline]
+	unsafe fn add_mod(a: __m128i, b: __m128i) -> __m128i {
+		let c: __m128i = _mm_add_epi32(a, b);
+		_mm_add_epi32(_mm_and_si128(c, _mm_set1_epi32(0x7FFFFFFF)), _mm_srli_epi32(c, 31))
+	}
+
+	#[inline]
+	unsafe fn mul_by_pow_2(x: __m128i, k: i32) -> __m128i {
+		let rotated: __m128i = _mm_or_si128(_mm_sll_epi32(x, _mm_cvtsi32_si128(k)), _mm_srl_epi32(x, _mm_cvtsi32_si128(31 - k)));
+		_mm_and_si128(rotated, _mm_set1_epi32(0x7FFFFFFF))
+	}
+
+	// Same round as Zuc_128_x4::round_scalar, S-boxes are still looked up lane by lane
+	#[target_feature(enable = "sse2")]
+	pub unsafe fn round(zuc: &mut Zuc_128_x4, init_mode: bool) -> [u32; LANES] {
+		let s = |i: usize| load(zuc.cell(i));
+		let low: __m128i = _mm_set1_epi32(0xFFFF);
+		let x0: __m128i = _mm_or_si128(_mm_slli_epi32(_mm_and_si128(s(15), _mm_set1_epi32(0x7FFF8000)), 1), _mm_and_si128(s(14), low));
+		let x1: __m128i = _mm_or_si128(_mm_slli_epi32(_mm_and_si128(s(11), low), 16), _mm_srli_epi32(s(9), 15));
+		let x2: __m128i = _mm_or_si128(_mm_slli_epi32(_mm_and_si128(s(7), low), 16), _mm_srli_epi32(s(5), 15));
+		let x3: __m128i = _mm_or_si128(_mm_slli_epi32(_mm_and_si128(s(2), low), 16), _mm_srli_epi32(s(0), 15));
+
+		let r1: __m128i = load(&zuc.f_r1);
+		let r2: __m128i = load(&zuc.f_r2);
+		let w: __m128i = _mm_add_epi32(_mm_xor_si128(x0, r1), r2);
+		let w1: __m128i = _mm_add_epi32(r1, x1);
+		let w2: __m128i = _mm_xor_si128(r2, x2);
+		let u: __m128i = l1(_mm_or_si128(_mm_slli_epi32(w1, 16), _mm_srli_epi32(w2, 16)));
+		let v: __m128i = l2(_mm_or_si128(_mm_slli_epi32(w2, 16), _mm_srli_epi32(w1, 16)));
+		let z: [u32; LANES] = store(_mm_xor_si128(w, x3));
+
+		let mut f: __m128i = s(0);
+		f = add_mod(f, mul_by_pow_2(s(0), 8));
+		f = add_mod(f, mul_by_pow_2(s(4), 20));
+		f = add_mod(f, mul_by_pow_2(s(10), 21));
+		f = add_mod(f, mul_by_pow_2(s(13), 17));
+		f = add_mod(f, mul_by_pow_2(s(15), 15));
+		if init_mode {
+			f = add_mod(f, _mm_srli_epi32(w, 1));
+		}
+		zuc.push(store(f));
+
+		zuc.f_r1 = store(u).map(s_box);
+		zuc.f_r2 = store(v).map(s_box);
+		z
+	}
+}
+
+#[cfg(test)]
+const TEST_KEYS: [[u8; 16]; LANES] = [
+	[0x00; 16],
+	[0xff; 16],
+	[0x3d, 0x4c, 0x4b, 0xe9, 0x6a, 0x82, 0xfd, 0xae, 0xb5, 0x8f, 0x64, 0x1d, 0xb1, 0x7b, 0x45, 0x5b],
+	[0x4d, 0x32, 0x0b, 0xfa, 0xd4, 0xc2, 0x85, 0xbf, 0xd6, 0xb8, 0xbd, 0x00, 0xf3, 0x9d, 0x8b, 0x41]];
+
+#[cfg(test)]
+const TEST_IVS: [[u8; 16]; LANES] = [
+	[0x00; 16],
+	[0xff; 16],
+	[0x84, 0x31, 0x9a, 0xa8, 0xde, 0x69, 0x15, 0xca, 0x1f, 0x6b, 0xda, 0x6b, 0xfb, 0xd8, 0xc7, 0x66],
+	[0x52, 0x95, 0x9d, 0xab, 0xa0, 0xbf, 0x17, 0x6e, 0xce, 0x2d, 0xc3, 0x15, 0x04, 0x9e, 0xb5, 0x74]];
+
+#[cfg(test)]
+fn check_against_zuc_128(mut zuc: Zuc_128_x4) {
+	use zuc_128::Zuc_128;
+
+	let mut buffers: [Vec<u32>; LANES] = [vec![0; 2000], vec![0; 2000], vec![0; 2000], vec![0; 2000]];
+	{
+		let [b0, b1, b2, b3] = &mut buffers;
+		zuc.generate_keystream([b0, b1, b2, b3], 2000);
+	}
+
+	for lane in 0..LANES {
+		let mut expected: Vec<u32> = vec![0; 2000];
+		Zuc_128::new(&TEST_KEYS[lane], &TEST_IVS[lane]).generate_keystream(&mut expected, 2000);
+		assert_eq!(buffers[lane], expected);
+	}
+
+	// Document 3: Implementor’s Test Data, test sets 1 to 4
+	assert_eq!([buffers[0][0], buffers[1][0], buffers[2][0], buffers[3][0]], [0x27bede74, 0x0657cfa0, 0x14f1c272, 0xed4400e7]);
+	assert_eq!(buffers[3][1999], 0x7a574cdb);
+}
+
+#[test]
+fn zuc_128_x4_matches_zuc_128() {
+	check_against_zuc_128(Zuc_128_x4::new(&TEST_KEYS, &TEST_IVS));
+}
+
+#[test]
+fn zuc_128_x4_scalar_matches_zuc_128() {
+	check_against_zuc_128(Zuc_128_x4::with_backend(&TEST_KEYS, &TEST_IVS, false));
+
+	//Backends can be switched between rounds
+	let mut keys: [[u8; 16]; LANES] = TEST_KEYS;
+	keys.reverse();
+	let mut ivs: [[u8; 16]; LANES] = TEST_IVS;
+	ivs.reverse();
+	let mut simd: Zuc_128_x4 = Zuc_128_x4::with_backend(&keys, &ivs, true);
+	let mut mixed: Zuc_128_x4 = Zuc_128_x4::with_backend(&keys, &ivs, false);
+	for i in 0..100 {
+		mixed.use_simd = i % 3 == 0;
+		assert_eq!(simd.next_words(), mixed.next_words());
+	}
+}
@@ -0,0 +1,232 @@
+// ZUC-256 stream cipher and its MAC, as proposed for 5G.
+// Only the key loading differs from ZUC-128, the rest of the machinery is shared.
+
+use zuc_128::{Zuc_128, ZucState, ZucStateError, ZucVariant};
+
+pub const KEY_LEN: usize = 32;
+// 17 bytes followed by 8 six-bit components, 184 bits in total
+pub const IV_LEN: usize = 25;
+
+// The constants D, a row for the keystream and one for every MAC tag length.
+const D: [[u8; 16]; 4] = [
+	[0x22, 0x2f, 0x24, 0x2a, 0x6d, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x52, 0x10, 0x30],
+	[0x22, 0x2f, 0x25, 0x2a, 0x6d, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x52, 0x10, 0x30],
+	[0x23, 0x2f, 0x24, 0x2a, 0x6d, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x52, 0x10, 0x30],
+	[0x23, 0x2f, 0x25, 0x2a, 0x6d, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x52, 0x10, 0x30]
+];
+
+#[allow(non_camel_case_types)]
+pub struct Zuc_256 {
+	zuc: Zuc_128,
+}
+
+impl Zuc_256 {
+	pub fn new(k: &[u8; KEY_LEN], iv: &[u8; IV_LEN]) -> Zuc_256 {
+		Zuc_256::with_constants(k, iv, &D[0])
+	}
+
+	fn with_constants(k: &[u8; KEY_LEN], iv: &[u8; IV_LEN], d: &[u8; 16]) -> Zuc_256 {
+		Zuc_256 {zuc: Zuc_128::with_lfsr(load_lfsr(k, iv, d))}
+	}
+
+	pub fn generate_keystream(&mut self, buffer: &mut[u32], length: usize) {
+		self.zuc.generate_keystream(buffer, length);
+	}
+
+	pub fn apply_keystream(&mut self, data: &mut [u8]) {
+		self.zuc.apply_keystream(data);
+	}
+
+	pub fn position(&self) -> u64 {
+		self.zuc.position()
+	}
+
+	pub fn seek_to_word(&mut self, n: u64) {
+		self.zuc.seek_to_word(n);
+	}
+
+	pub fn save_state(&self) -> ZucState {
+		self.zuc.save_state_as(ZucVariant::Zuc256)
+	}
+
+	// The key and IV are needed again to rewind with seek_to_word, see Zuc_128::from_state
+	pub fn from_state(state: &ZucState, k: &[u8; KEY_LEN], iv: &[u8; IV_LEN]) -> Result<Zuc_256, ZucStateError> {
+		Ok(Zuc_256 {zuc: Zuc_128::with_state(state, ZucVariant::Zuc256, load_lfsr(k, iv, &D[0]))?})
+	}
+}
+
+impl Iterator for Zuc_256 {
+	type Item = u32;
+
+	fn next(&mut self) -> Option<u32> {
+		self.zuc.next()
+	}
+}
+
+// tag_len is 4, 8 or 16 bytes, length is the message length in bits.
+// Tag starts as the first tag bits of the keystream, then the keystream window
+// after it is added for every set bit of the message and for the final position.
+pub fn zuc_256_mac(key: &[u8; KEY_LEN], iv: &[u8; IV_LEN], tag_len: usize, length: usize, message: &[u8]) -> Vec<u8> {
+	let d: &[u8; 16] = match tag_len {
+		4  => &D[1],
+		8  => &D[2],
+		16 => &D[3],
+		_  => panic!("Tag length must be 4, 8 or 16 bytes!"),
+	};
+	assert!(message.len() * 8 >= length, "Message is shorter than {0} bits!", length);
+
+	let tag_bits: usize = tag_len * 8;
+	let words_count: usize = (length + 2 * tag_bits).div_ceil(32);
+	let mut keystream: Vec<u32> = vec![0; words_count];
+	let mut zuc: Zuc_256 = Zuc_256::with_constants(key, iv, d);
+	zuc.generate_keystream(&mut keystream, words_count);
+
+	// tag_bits of the keystream starting from bit i
+	let word = |j: usize| -> u128 { *keystream.get(j).unwrap_or(&0) as u128 };
+	let window = |i: usize| -> u128 {
+		let j: usize = i / 32;
+		let high: u128 = (word(j) << 96) | (word(j + 1) << 64) | (word(j + 2) << 32) | word(j + 3);
+		let shift: usize = i % 32;
+		let bits: u128 = if shift == 0 { high } else { (high << shift) | (word(j + 4) >> (32 - shift)) };
+		bits >> (128 - tag_bits)
+	};
+
+	let mut tag: u128 = window(0);
+	for i in 0..length {
+		if (message[i / 8] >> (7 - i % 8)) & 1 == 1 {
+			tag ^= window(tag_bits + i);
+		}
+	}
+	tag ^= window(tag_bits + length);
+	tag.to_be_bytes()[16 - tag_len..].to_vec()
+}
+
+fn load_lfsr(k: &[u8; KEY_LEN], iv: &[u8; IV_LEN], d: &[u8; 16]) -> [u32; 16] {
+	assert!(iv[17..].iter().all(|&x| x < 0x40), "IV components 17..24 must have 6 bits!");
+
+	[
+		make_u31(k[0],   d[0],           k[21],  k[16]),
+		make_u31(k[1],   d[1],           k[22],  k[17]),
+		make_u31(k[2],   d[2],           k[23],  k[18]),
+		make_u31(k[3],   d[3],           k[24],  k[19]),
+		make_u31(k[4],   d[4],           k[25],  k[20]),
+		make_u31(iv[0],  d[5] | iv[17],  k[5],   k[26]),
+		make_u31(iv[1],  d[6] | iv[18],  k[6],   k[27]),
+		make_u31(iv[10], d[7] | iv[19],  k[7],   iv[2]),
+		make_u31(k[8],   d[8] | iv[20],  iv[3],  iv[11]),
+		make_u31(k[9],   d[9] | iv[21],  iv[12], iv[4]),
+		make_u31(iv[5],  d[10] | iv[22], k[10],  k[28]),
+		make_u31(k[11],  d[11] | iv[23], iv[6],  iv[13]),
+		make_u31(k[12],  d[12] | iv[24], iv[7],  iv[14]),
+		make_u31(k[13],  d[13],          iv[15], iv[8]),
+		make_u31(k[14],  d[14] | (k[31] >> 4),   iv[16], iv[9]),
+		make_u31(k[15],  d[15] | (k[31] & 0x0f), k[30],  k[29]),
+	]
+}
+
+#[inline]
+fn make_u31(a: u8, b: u8, c: u8, d: u8) -> u32 {
+	((a as u32) << 23) | ((b as u32) << 16) | ((c as u32) << 8) | (d as u32)
+}
+
+#[cfg(test)]
+const TEST_IV_FF: [u8; IV_LEN] = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                                  0xff, 0xff, 0xff, 0xff, 0xff, 0x3f, 0x3f, 0x3f, 0x3f, 0x3f, 0x3f, 0x3f, 0x3f];
+
+// The ZUC-256 Stream Cipher, test vectors for keystream generation
+#[test]
+fn zuc_256_keystream_1() {
+	let key: [u8; KEY_LEN] = [0; KEY_LEN];
+	let iv: [u8; IV_LEN] = [0; IV_LEN];
+	let expected: [u32; 20] = [
+		0x58d03ad6, 0x2e032ce2, 0xdafc683a, 0x39bdcb03, 0x52a2bc67, 0xf1b7de74, 0x163ce3a1, 0x01ef5558,
+		0x9639d75b, 0x95fa681b, 0x7f090df7, 0x56391ccc, 0x903b7612, 0x744d544c, 0x17bc3fad, 0x8b163b08,
+		0x21787c0b, 0x97775bb8, 0x4943c6bb, 0xe8ad8afd];
+
+	let mut keystream: [u32; 20] = [0; 20];
+	let mut zuc: Zuc_256 = Zuc_256::new(&key, &iv);
+	zuc.generate_keystream(&mut keystream, 20);
+	assert_eq!(keystream, expected);
+}
+
+#[test]
+fn zuc_256_keystream_2() {
+	let key: [u8; KEY_LEN] = [0xff; KEY_LEN];
+	let expected: [u32; 20] = [
+		0x3356cbae, 0xd1a1c18b, 0x6baa4ffe, 0x343f777c, 0x9e15128f, 0x251ab65b, 0x949f7b26, 0xef7157f2,
+		0x96dd2fa9, 0xdf95e3ee, 0x7a5be02e, 0xc32ba585, 0x505af316, 0xc2f9ded2, 0x7cdbd935, 0xe441ce11,
+		0x15fd0a80, 0xbb7aef67, 0x68989416, 0xb8fac8c2];
+
+	let mut keystream: [u32; 20] = [0; 20];
+	let mut zuc: Zuc_256 = Zuc_256::new(&key, &TEST_IV_FF);
+	zuc.generate_keystream(&mut keystream, 20);
+	assert_eq!(keystream, expected);
+
+	zuc.seek_to_word(5);
+	let state: ZucState = zuc.save_state();
+	assert_eq!(zuc.take(15).collect::<Vec<u32>>(), &expected[5..]);
+
+	let mut resumed: Zuc_256 = Zuc_256::from_state(&state, &key, &TEST_IV_FF).unwrap();
+	assert_eq!(resumed.next(), Some(expected[5]));
+	resumed.seek_to_word(0);
+	assert_eq!(resumed.take(20).collect::<Vec<u32>>(), &expected[..]);
+}
+
+#[test]
+fn zuc_256_state_cipher_check() {
+	let key: [u8; KEY_LEN] = [0xff; KEY_LEN];
+	let zuc: Zuc_256 = Zuc_256::new(&key, &TEST_IV_FF);
+	let state: ZucState = zuc.save_state();
+	assert_eq!(format!("{:?}", state), "ZucState { variant: Zuc256, position: 0, .. }");
+	assert_eq!(Zuc_128::from_state(&state, &[0xff; 16], &[0xff; 16]).err(), Some(ZucStateError::WrongCipher));
+
+	let zuc: Zuc_128 = Zuc_128::new(&[0xff; 16], &[0xff; 16]);
+	assert_eq!(Zuc_256::from_state(&zuc.save_state(), &key, &TEST_IV_FF).err(), Some(ZucStateError::WrongCipher));
+}
+
+// The ZUC-256 Stream Cipher, test vectors for MAC generation
+#[test]
+fn zuc_256_mac_1() {
+	let key: [u8; KEY_LEN] = [0; KEY_LEN];
+	let iv: [u8; IV_LEN] = [0; IV_LEN];
+	let message: [u8; 50] = [0; 50];
+
+	assert_eq!(zuc_256_mac(&key, &iv, 4, 400, &message), [0x9b, 0x97, 0x2a, 0x74]);
+	assert_eq!(zuc_256_mac(&key, &iv, 8, 400, &message), [0x67, 0x3e, 0x54, 0x99, 0x00, 0x34, 0xd3, 0x8c]);
+	assert_eq!(zuc_256_mac(&key, &iv, 16, 400, &message),
+	           [0xd8, 0x5e, 0x54, 0xbb, 0xcb, 0x96, 0x00, 0x96, 0x70, 0x84, 0xc9, 0x52, 0xa1, 0x65, 0x4b, 0x26]);
+}
+
+#[test]
+fn zuc_256_mac_2() {
+	let key: [u8; KEY_LEN] = [0; KEY_LEN];
+	let iv: [u8; IV_LEN] = [0; IV_LEN];
+	let message: [u8; 500] = [0x11; 500];
+
+	assert_eq!(zuc_256_mac(&key, &iv, 4, 4000, &message), [0x87, 0x54, 0xf5, 0xcf]);
+	assert_eq!(zuc_256_mac(&key, &iv, 8, 4000, &message), [0x13, 0x0d, 0xc2, 0x25, 0xe7, 0x22, 0x40, 0xcc]);
+	assert_eq!(zuc_256_mac(&key, &iv, 16, 4000, &message),
+	           [0xdf, 0x1e, 0x83, 0x07, 0xb3, 0x1c, 0xc6, 0x2b, 0xec, 0xa1, 0xac, 0x6f, 0x81, 0x90, 0xc2, 0x2f]);
+}
+
+#[test]
+fn zuc_256_mac_3() {
+	let key: [u8; KEY_LEN] = [0xff; KEY_LEN];
+	let message: [u8; 50] = [0; 50];
+
+	assert_eq!(zuc_256_mac(&key, &TEST_IV_FF, 4, 400, &message), [0x1f, 0x30, 0x79, 0xb4]);
+	assert_eq!(zuc_256_mac(&key, &TEST_IV_FF, 8, 400, &message), [0x8c, 0x71, 0x39, 0x4d, 0x39, 0x95, 0x77, 0x25]);
+	assert_eq!(zuc_256_mac(&key, &TEST_IV_FF, 16, 400, &message),
+	           [0xa3, 0x5b, 0xb2, 0x74, 0xb5, 0x67, 0xc4, 0x8b, 0x28, 0x31, 0x9f, 0x11, 0x1a, 0xf3, 0x4f, 0xbd]);
+}
+
+#[test]
+fn zuc_256_mac_4() {
+	let key: [u8; KEY_LEN] = [0xff; KEY_LEN];
+	let message: [u8; 500] = [0x11; 500];
+
+	assert_eq!(zuc_256_mac(&key, &TEST_IV_FF, 4, 4000, &message), [0x5c, 0x7c, 0x8b, 0x88]);
+	assert_eq!(zuc_256_mac(&key, &TEST_IV_FF, 8, 4000, &message), [0xea, 0x1d, 0xee, 0x54, 0x4b, 0xb6, 0x22, 0x3b]);
+	assert_eq!(zuc_256_mac(&key, &TEST_IV_FF, 16, 4000, &message),
+	           [0x3a, 0x83, 0xb5, 0x54, 0xbe, 0x40, 0x8c, 0xa5, 0x49, 0x41, 0x24, 0xed, 0x9d, 0x47, 0x32, 0x05]);
+}